@@ -0,0 +1,143 @@
+use crate::{read::Diagnostic, report::Report};
+
+fn unjustified(report: &Report) -> impl Iterator<Item = &Diagnostic> {
+    report
+        .diagnostics
+        .iter()
+        .filter(|d| report.justification(d).is_none())
+}
+
+fn text(d: &Diagnostic) -> String {
+    format!("{}: {} ({})", d.Moniker, d.Message, d.Path)
+}
+
+fn azure_escape(text: &str) -> String {
+    text.replace('%', "%AZP25")
+        .replace(';', "%3B")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+        .replace(']', "%5D")
+}
+
+fn github_escape(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn github_escape_property(text: &str) -> String {
+    github_escape(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Azure DevOps `##vso[task.logissue]` logging commands, one per unjustified
+/// diagnostic.
+pub fn to_azure(report: &Report) -> Result<String, String> {
+    let lines: Vec<String> = unjustified(report)
+        .map(|d| {
+            let kind = match d.Severity.as_str() {
+                "Error" => "error",
+                _ => "warning",
+            };
+            let source = match report.location(d) {
+                Some(l) => format!(
+                    "sourcepath={};linenumber={};",
+                    azure_escape(&l.file.to_string_lossy()),
+                    l.line
+                ),
+                None => String::new(),
+            };
+            format!(
+                "##vso[task.logissue type={kind};{source}code={};]{}",
+                azure_escape(&d.Moniker),
+                azure_escape(&text(d))
+            )
+        })
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+/// GitHub Actions workflow commands, one per unjustified diagnostic.
+pub fn to_github(report: &Report) -> Result<String, String> {
+    let lines: Vec<String> = unjustified(report)
+        .map(|d| {
+            let kind = match d.Severity.as_str() {
+                "Error" => "error",
+                "Warning" => "warning",
+                _ => "notice",
+            };
+            let file = match report.location(d) {
+                Some(l) => format!(
+                    "file={},line={},",
+                    github_escape_property(&l.file.to_string_lossy()),
+                    l.line
+                ),
+                None => String::new(),
+            };
+            format!(
+                "::{kind} {file}title={}::{}",
+                github_escape_property(&d.Moniker),
+                github_escape(&text(d))
+            )
+        })
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::catalogue::Catalogue;
+
+    #[test]
+    fn escapes_azure_logging_commands() {
+        assert_eq!(
+            azure_escape("100% done;\r\nsee [a]"),
+            "100%AZP25 done%3B%0D%0Asee [a%5D"
+        );
+        assert_eq!(azure_escape("%3B"), "%AZP253B");
+    }
+
+    #[test]
+    fn escapes_github_workflow_commands() {
+        assert_eq!(
+            github_escape("100% done:\r\na, b"),
+            "100%25 done:%0D%0Aa, b"
+        );
+        assert_eq!(
+            github_escape_property(r"C:\Ax\A.xml, 50%"),
+            r"C%3A\Ax\A.xml%2C 50%25"
+        );
+        assert_eq!(github_escape_property("a\nb:c"), "a%0Ab%3Ac");
+    }
+
+    #[test]
+    fn reports_unjustified_diagnostics() {
+        let diagnostic = |moniker: &str, justification: &str| Diagnostic {
+            DiagnosticType: "BestPractices".to_owned(),
+            Severity: "Error".to_owned(),
+            ElementType: "Class".to_owned(),
+            Path: "dynamics://Class/A".to_owned(),
+            Moniker: moniker.to_owned(),
+            Message: "50%; done".to_owned(),
+            Justification: justification.to_owned(),
+        };
+        let report = Report {
+            model: "MyModel".to_owned(),
+            diagnostics: vec![diagnostic("BPOpen", ""), diagnostic("BPDone", "Fine")],
+            suppressions: vec![],
+            metadata_dir: PathBuf::new(),
+            catalogue: Catalogue::default(),
+            must_fix: vec![],
+        };
+        assert_eq!(
+            to_azure(&report).unwrap(),
+            "##vso[task.logissue type=error;code=BPOpen;]BPOpen: 50%AZP25%3B done (dynamics://Class/A)"
+        );
+        assert_eq!(
+            to_github(&report).unwrap(),
+            "::error title=BPOpen::BPOpen: 50%25; done (dynamics://Class/A)"
+        );
+    }
+}
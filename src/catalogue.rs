@@ -0,0 +1,51 @@
+use serde::Deserialize;
+
+use crate::config::Config;
+
+const BUILT_IN: &str = include_str!("../data/bp_rules.toml");
+
+/// Description of a best practice rule.
+#[derive(Deserialize, Clone)]
+pub struct RuleInfo {
+    pub moniker: String,
+    pub title: String,
+    #[serde(default)]
+    pub explanation: String,
+    #[serde(default)]
+    pub fix: String,
+    #[serde(default = "suppressible_default")]
+    pub suppressible: bool,
+}
+
+fn suppressible_default() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct CatalogueFile {
+    rule_info: Vec<RuleInfo>,
+}
+
+/// The built-in rule descriptions, extended and overridden by `[[rule_info]]`
+/// entries in the config.
+#[derive(Default)]
+pub struct Catalogue {
+    rules: Vec<RuleInfo>,
+}
+
+impl Catalogue {
+    pub fn new(config: &Config) -> Self {
+        let mut rules = toml::from_str::<CatalogueFile>(BUILT_IN)
+            .map(|c| c.rule_info)
+            .unwrap_or_default();
+        for info in &config.rule_info {
+            rules.retain(|r| r.moniker != info.moniker);
+            rules.push(info.clone());
+        }
+        Self { rules }
+    }
+
+    pub fn get(&self, moniker: &str) -> Option<&RuleInfo> {
+        self.rules.iter().find(|r| r.moniker == moniker)
+    }
+}
@@ -0,0 +1,25 @@
+use crate::{config::Config, meta::SuppressionMeta, policy::Policy, report::Report};
+
+/// Problems that should fail a build: diagnostics of must-fix rules,
+/// diagnostics without a justification and suppressions that have expired.
+pub fn check(config: &Config, report: &Report, meta: &[SuppressionMeta]) -> Vec<String> {
+    let mut problems = vec![];
+    for d in &report.diagnostics {
+        if Policy::of(config, &report.model, &d.Moniker) == Policy::MustFix {
+            problems.push(format!("{} {}: must be fixed", d.Moniker, d.Path));
+        } else if report.justification(d).is_none() {
+            problems.push(format!("{} {}: not justified", d.Moniker, d.Path));
+        }
+    }
+    for s in &report.suppressions {
+        if let Some(m) = meta.iter().find(|m| m.is_for(s) && m.is_expired()) {
+            problems.push(format!(
+                "{} {}: suppression expired on {}",
+                s.Moniker,
+                s.Path,
+                m.expires.as_deref().unwrap_or_default()
+            ));
+        }
+    }
+    problems
+}
@@ -0,0 +1,457 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{
+    check::check,
+    config::Config,
+    json::{diagnostics_to_json, to_json, value_to_json, JsonFormat, Stats},
+    lint::Linter,
+    meta::{apply_drafts, read_meta, save_drafts, set_state, ReviewState},
+    owners::Owners,
+    policy::Policy,
+    read::{read_actionable_diagnostics, Diagnostic},
+    report::{render_report, Report, ReportFormat},
+    rules::{apply_rules, read_rules},
+    write::{read_suppressions, write_diagnostics},
+};
+
+/// Options that don't take a value.
+const FLAGS: [&str; 2] = ["dry-run", "force"];
+
+pub const USAGE: &str = "Usage:
+  bp-manager                                         start the interactive app
+  bp-manager diagnostics <model> [--format json|jsonl] [--owner <owner>]
+  bp-manager suppressions <model> [--format json|jsonl]
+  bp-manager stats <model> [--format json|jsonl] [--owner <owner>]
+  bp-manager report <model> --format sarif|junit|html|markdown|azure|github [--owner <owner>]
+  bp-manager report <model> --format <format> --per-owner <dir>
+  bp-manager audit <model> [--format json|jsonl]
+  bp-manager justify <model> --rules <rules.toml> [--work-item <id>] [--dry-run] [--force]
+  bp-manager lint <model>
+  bp-manager check <model>
+
+All commands take --profile <name> to use a profile from config.toml.";
+
+pub enum Command {
+    Tui,
+    Help,
+    Diagnostics {
+        model: String,
+        format: JsonFormat,
+        owner: Option<String>,
+    },
+    Suppressions {
+        model: String,
+        format: JsonFormat,
+    },
+    Stats {
+        model: String,
+        format: JsonFormat,
+        owner: Option<String>,
+    },
+    Report {
+        model: String,
+        format: ReportFormat,
+        owner: Option<String>,
+        /// Folder to write a report per owner to.
+        per_owner: Option<String>,
+    },
+    Audit {
+        model: String,
+        format: JsonFormat,
+    },
+    Justify {
+        model: String,
+        rules: String,
+        work_item: Option<String>,
+        dry_run: bool,
+        force: bool,
+    },
+    Lint {
+        model: String,
+    },
+    Check {
+        model: String,
+    },
+}
+
+/// Positional arguments, `--name value` / `--name=value` options and flags.
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+    flags: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut positional = vec![];
+        let mut options = HashMap::new();
+        let mut flags = vec![];
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.strip_prefix("--") {
+                Some(flag) if FLAGS.contains(&flag) => flags.push(flag.to_owned()),
+                Some(option) => match option.split_once('=') {
+                    Some((name, value)) => {
+                        options.insert(name.to_owned(), value.to_owned());
+                    }
+                    None => match iter.next() {
+                        Some(value) => {
+                            options.insert(option.to_owned(), value.clone());
+                        }
+                        None => return Err(format!("Missing value for --{option}")),
+                    },
+                },
+                None => positional.push(arg.clone()),
+            }
+        }
+        Ok(Self {
+            positional,
+            options,
+            flags,
+        })
+    }
+
+    fn model(&self) -> Result<String, String> {
+        match self.positional.get(1) {
+            Some(m) => Ok(m.clone()),
+            None => Err(format!("Missing model for {}", self.positional[0])),
+        }
+    }
+
+    fn option(&self, name: &str) -> Result<String, String> {
+        match self.options.get(name) {
+            Some(value) => Ok(value.clone()),
+            None => Err(format!("Missing --{name} for {}", self.positional[0])),
+        }
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|f| f == name)
+    }
+
+    fn json_format(&self) -> Result<JsonFormat, String> {
+        match self.options.get("format").map(|f| f.as_str()) {
+            None | Some("json") => Ok(JsonFormat::Json),
+            Some("jsonl") => Ok(JsonFormat::JsonLines),
+            Some(f) => Err(format!("Unknown format: {f}")),
+        }
+    }
+
+    fn report_format(&self) -> Result<ReportFormat, String> {
+        match self.options.get("format") {
+            Some(f) => ReportFormat::parse(f),
+            None => Err("Missing --format for report".to_owned()),
+        }
+    }
+}
+
+/// The command and the `--profile` it runs with.
+pub fn parse_args(args: &[String]) -> Result<(Command, Option<String>), String> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        return Ok((Command::Help, None));
+    }
+    let args = Args::parse(args)?;
+    let command = match args.positional.first().map(|c| c.as_str()) {
+        None => Command::Tui,
+        Some("help") => Command::Help,
+        Some("diagnostics") => Command::Diagnostics {
+            model: args.model()?,
+            format: args.json_format()?,
+            owner: args.options.get("owner").cloned(),
+        },
+        Some("suppressions") => Command::Suppressions {
+            model: args.model()?,
+            format: args.json_format()?,
+        },
+        Some("stats") => Command::Stats {
+            model: args.model()?,
+            format: args.json_format()?,
+            owner: args.options.get("owner").cloned(),
+        },
+        Some("report") => {
+            let owner = args.options.get("owner").cloned();
+            let per_owner = args.options.get("per-owner").cloned();
+            if owner.is_some() && per_owner.is_some() {
+                return Err("Use either --owner or --per-owner".to_owned());
+            }
+            Command::Report {
+                model: args.model()?,
+                format: args.report_format()?,
+                owner,
+                per_owner,
+            }
+        }
+        Some("audit") => Command::Audit {
+            model: args.model()?,
+            format: args.json_format()?,
+        },
+        Some("justify") => Command::Justify {
+            model: args.model()?,
+            rules: args.option("rules")?,
+            work_item: args.options.get("work-item").cloned(),
+            dry_run: args.flag("dry-run"),
+            force: args.flag("force"),
+        },
+        Some("lint") => Command::Lint {
+            model: args.model()?,
+        },
+        Some("check") => Command::Check {
+            model: args.model()?,
+        },
+        Some(c) => return Err(format!("Unknown command: {c}")),
+    };
+    Ok((command, args.options.get("profile").cloned()))
+}
+
+/// Runs a non-interactive command and returns what should be printed.
+pub fn run(command: &Command, config: &Config) -> Result<String, String> {
+    if config.needs_profile() {
+        let names: Vec<&str> = config.profiles.keys().map(|p| p.as_str()).collect();
+        return Err(format!(
+            "Choose a profile with --profile: {}",
+            names.join(", ")
+        ));
+    }
+    match command {
+        Command::Tui => Ok(String::new()),
+        Command::Help => Ok(USAGE.to_owned()),
+        Command::Diagnostics {
+            model,
+            format,
+            owner,
+        } => {
+            let mut data = read_actionable_diagnostics(config, model)?;
+            if let Some(owner) = owner {
+                let owners = Owners::read(config)?;
+                data.retain(|d| owners.is_owned_by(&d.Path, owner));
+            }
+            diagnostics_to_json(&data, *format)
+        }
+        Command::Suppressions { model, format } => {
+            diagnostics_to_json(&read_suppressions(config, model)?, *format)
+        }
+        Command::Stats {
+            model,
+            format,
+            owner,
+        } => value_to_json(&Stats::new(&read_report(config, model, owner)?), *format),
+        Command::Report {
+            model,
+            format,
+            per_owner: Some(dir),
+            ..
+        } => write_owner_reports(config, model, *format, dir),
+        Command::Report {
+            model,
+            format,
+            owner,
+            per_owner: None,
+        } => render_report(&read_report(config, model, owner)?, *format),
+        Command::Audit { model, format } => to_json(&read_meta(config, model)?, *format),
+        Command::Justify {
+            model,
+            rules,
+            work_item,
+            dry_run,
+            force,
+        } => justify(config, model, rules, work_item.as_deref(), *dry_run, *force),
+        Command::Lint { model } => {
+            let suppressions = read_suppressions(config, model)?;
+            let problems = Linter::new(&config.lint)?.check_all(&suppressions);
+            match problems.is_empty() {
+                true => Ok(format!(
+                    "No problems in {} suppressions",
+                    suppressions.len()
+                )),
+                false => Err(problems.join("\n")),
+            }
+        }
+        Command::Check { model } => {
+            let report = Report::read(config, model)?;
+            let problems = check(config, &report, &read_meta(config, model)?);
+            match problems.is_empty() {
+                true => Ok(format!(
+                    "No problems in {} diagnostics",
+                    report.diagnostics.len()
+                )),
+                false => Err(problems.join("\n")),
+            }
+        }
+    }
+}
+
+/// Reads the report, limited to the diagnostics of one owner if given.
+fn read_report(config: &Config, model: &String, owner: &Option<String>) -> Result<Report, String> {
+    let mut report = Report::read(config, model)?;
+    if let Some(owner) = owner {
+        let owners = Owners::read(config)?;
+        report
+            .diagnostics
+            .retain(|d| owners.is_owned_by(&d.Path, owner));
+    }
+    Ok(report)
+}
+
+/// Writes a report for every owner in the owners file, and one for the
+/// diagnostics nobody owns if there are any, to `dir` as
+/// `<model>_<owner>.<ext>`. Returns the files written, one per line.
+fn write_owner_reports(
+    config: &Config,
+    model: &String,
+    format: ReportFormat,
+    dir: &str,
+) -> Result<String, String> {
+    if config.owners.is_none() {
+        return Err("Set owners in config.toml to write a report per owner".to_owned());
+    }
+    let owners = Owners::read(config)?;
+    let mut report = Report::read(config, model)?;
+    let all = std::mem::take(&mut report.diagnostics);
+    let mut groups: Vec<(&str, Vec<Diagnostic>)> = owners
+        .all()
+        .into_iter()
+        .map(|owner| {
+            let owned = all
+                .iter()
+                .filter(|d| owners.is_owned_by(&d.Path, owner))
+                .cloned()
+                .collect();
+            (owner, owned)
+        })
+        .collect();
+    let unowned: Vec<Diagnostic> = all
+        .iter()
+        .filter(|d| owners.owners_of(&d.Path).is_empty())
+        .cloned()
+        .collect();
+    if !unowned.is_empty() {
+        groups.push(("unowned", unowned));
+    }
+    if fs::create_dir_all(dir).is_err() {
+        return Err(format!("Could not create {dir}"));
+    }
+    let mut files = vec![];
+    for (owner, diagnostics) in groups {
+        report.diagnostics = diagnostics;
+        let file = Path::new(dir).join(format!(
+            "{model}_{}.{}",
+            owner_file_name(owner),
+            format.extension()
+        ));
+        if fs::write(&file, render_report(&report, format)?).is_err() {
+            return Err(format!("Could not write {}", file.display()));
+        }
+        files.push(file.display().to_string());
+    }
+    Ok(files.join("\n"))
+}
+
+/// An owner as part of a file name, e.g. `org-team` for `@org/team`.
+fn owner_file_name(owner: &str) -> String {
+    owner
+        .trim_start_matches('@')
+        .chars()
+        .map(|c| match c.is_alphanumeric() || "-_.".contains(c) {
+            true => c,
+            false => '-',
+        })
+        .collect()
+}
+
+fn justify(
+    config: &Config,
+    model: &String,
+    rules: &str,
+    work_item: Option<&str>,
+    dry_run: bool,
+    force: bool,
+) -> Result<String, String> {
+    let rules = read_rules(rules)?;
+    let mut data = read_actionable_diagnostics(config, model)?;
+    // Rules never justify what has to be fixed.
+    data.retain(|d| Policy::of(config, model, &d.Moniker) != Policy::MustFix);
+    if config.workflow {
+        // Justifications under review aren't replaced by the rules.
+        apply_drafts(&read_meta(config, model)?, &mut data);
+    }
+    let before: Vec<bool> = data.iter().map(|d| d.Justification.is_empty()).collect();
+    let suppressions = read_suppressions(config, model)?;
+    let count = apply_rules(&rules, &mut data, &suppressions)?;
+    let problems = Linter::new(&config.lint)?.check_all(&data);
+    if !problems.is_empty() && !force && !dry_run {
+        return Err(format!(
+            "{}\nNot written, justifications have problems (use --force to write anyway)",
+            problems.join("\n")
+        ));
+    }
+    if !dry_run && config.workflow {
+        // Rule based justifications go up for review like manual ones.
+        // Only drafts just created from the rules go up, approved and
+        // rejected justifications keep their state.
+        let meta = save_drafts(config, model, &data, &suppressions, work_item)?;
+        let justified: Vec<&Diagnostic> = data
+            .iter()
+            .zip(&before)
+            .filter(|(d, empty)| **empty && !d.Justification.is_empty())
+            .map(|(d, _)| d)
+            .filter(|d| {
+                meta.iter().any(|m| {
+                    m.is_for(d)
+                        && m.state == Some(ReviewState::Draft)
+                        && m.justification.as_deref() == Some(d.Justification.as_str())
+                })
+            })
+            .collect();
+        set_state(config, model, &justified, ReviewState::Proposed)?;
+    }
+    if !dry_run {
+        write_diagnostics(&data, config, model, work_item)?;
+    }
+    let mut lines: Vec<String> = data
+        .iter()
+        .zip(before)
+        .filter(|(d, empty)| *empty && !d.Justification.is_empty())
+        .map(|(d, _)| format!("{} {}: {}", d.Moniker, d.Path, d.Justification))
+        .collect();
+    if dry_run {
+        lines.extend(problems);
+    }
+    lines.push(match (dry_run, config.workflow) {
+        (true, _) => format!("Would justify {count} diagnostics in {model}"),
+        (false, true) => format!("Proposed {count} justifications in {model}"),
+        (false, false) => format!("Justified {count} diagnostics in {model}"),
+    });
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Command, String> {
+        let args: Vec<String> = args.split(' ').map(|a| a.to_owned()).collect();
+        parse_args(&args).map(|(command, _)| command)
+    }
+
+    #[test]
+    fn parses_reports_per_owner() {
+        assert!(matches!(
+            parse("report MyModel --format sarif --per-owner out"),
+            Ok(Command::Report { owner: None, per_owner: Some(dir), .. }) if dir == "out"
+        ));
+        assert_eq!(
+            parse("report MyModel --format sarif --per-owner out --owner @core").err(),
+            Some("Use either --owner or --per-owner".to_owned())
+        );
+    }
+
+    #[test]
+    fn names_files_after_owners() {
+        assert_eq!(owner_file_name("@sales"), "sales");
+        assert_eq!(owner_file_name("@org/team"), "org-team");
+        assert_eq!(
+            owner_file_name("jane.doe@example.com"),
+            "jane.doe-example.com"
+        );
+    }
+}
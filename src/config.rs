@@ -1,26 +1,210 @@
-use std::{fs, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
 
 use serde::Deserialize;
 
+use crate::{
+    catalogue::RuleInfo,
+    keys::KeyBindings,
+    lint::{LintConfig, Linter},
+    policy::Policy,
+    snippets::Snippet,
+    theme::Theme,
+};
+
 #[derive(Deserialize, Default)]
 pub struct Config {
+    #[serde(default)]
+    pub modelpath: String,
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// `[profile.<name>]` sections with their own model path and models,
+    /// e.g. for a dev VM and the build output on a network share.
+    #[serde(default, rename = "profile")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// The profile in use, if any.
+    #[serde(skip)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub snippets: Vec<Snippet>,
+    #[serde(default)]
+    pub lint: LintConfig,
+    /// Justifications need approval before they are written.
+    #[serde(default)]
+    pub workflow: bool,
+    /// CODEOWNERS-style file mapping paths to owners.
+    pub owners: Option<String>,
+    /// Command to open metadata files with, e.g. `code -g {file}:{line}`.
+    pub editor: Option<String>,
+    /// Additional or overridden best practice rule descriptions.
+    #[serde(default)]
+    pub rule_info: Vec<RuleInfo>,
+    /// Policy per moniker: must-fix, suppress-allowed or auto-ignore.
+    #[serde(default)]
+    pub policy: HashMap<String, Policy>,
+    /// Theme to start with until another is chosen in the app.
+    pub theme: Option<String>,
+    /// Additional themes, or replacements for built-in ones.
+    #[serde(default)]
+    pub themes: Vec<Theme>,
+    /// `[keys.<mode>]` sections mapping actions to keys.
+    #[serde(default)]
+    pub keys: HashMap<String, HashMap<String, Vec<String>>>,
+    /// `[model.<name>]` sections overriding the settings above per model.
+    #[serde(default, rename = "model")]
+    pub overrides: HashMap<String, ModelConfig>,
+}
+
+#[derive(Deserialize)]
+pub struct Profile {
     pub modelpath: String,
     pub models: Vec<String>,
+    /// `[profile.<name>.model.<model>]` sections with the model paths of
+    /// models stored elsewhere in this environment.
+    #[serde(default, rename = "model")]
+    pub overrides: HashMap<String, ProfileModel>,
 }
 
-pub fn read_config() -> Result<Config, String> {
+#[derive(Deserialize)]
+pub struct ProfileModel {
+    pub modelpath: String,
+}
+
+/// Settings for a single model, for models that follow other rules than the
+/// rest.
+#[derive(Deserialize, Default)]
+pub struct ModelConfig {
+    pub modelpath: Option<String>,
+    /// Suppression xml file to use instead of the one in the model's
+    /// `AxIgnoreDiagnosticList` folder.
+    pub suppressions: Option<String>,
+    /// Severities to show, e.g. `['Error', 'Warning']`. All but
+    /// informational by default.
+    pub severities: Option<Vec<String>>,
+    /// Policy per moniker, on top of the top level `[policy]`.
+    #[serde(default)]
+    pub policy: HashMap<String, Policy>,
+}
+
+impl Config {
+    /// Switches to the model path and models of the profile.
+    pub fn use_profile(&mut self, name: &str) -> Result<(), String> {
+        let Some(profile) = self.profiles.get(name) else {
+            return Err(format!("Profile {name} is not in config"));
+        };
+        if !Path::new(&profile.modelpath).exists() {
+            return Err(format!("Model path of profile {name} doesn't exist"));
+        }
+        self.modelpath = profile.modelpath.clone();
+        self.models = profile.models.clone();
+        // Model paths from `[model.<name>]` belong to the environment without
+        // a profile, so the profile's own replace them.
+        for settings in self.overrides.values_mut() {
+            settings.modelpath = None;
+        }
+        for (model, settings) in &profile.overrides {
+            self.overrides.entry(model.clone()).or_default().modelpath =
+                Some(settings.modelpath.clone());
+        }
+        self.profile = Some(name.to_owned());
+        Ok(())
+    }
+
+    /// Profiles are configured but none is chosen yet.
+    pub fn needs_profile(&self) -> bool {
+        self.profile.is_none() && self.modelpath.is_empty() && !self.profiles.is_empty()
+    }
+
+    /// Folder holding the model's package: the model's own path in the
+    /// profile in use, or without profiles in `[model.<name>]`, otherwise
+    /// the common one.
+    pub fn modelpath(&self, model: &str) -> &str {
+        self.overrides
+            .get(model)
+            .and_then(|m| m.modelpath.as_deref())
+            .unwrap_or(&self.modelpath)
+    }
+
+    pub fn suppressions(&self, model: &str) -> Option<&str> {
+        self.overrides
+            .get(model)
+            .and_then(|m| m.suppressions.as_deref())
+    }
+
+    pub fn shows_severity(&self, model: &str, severity: &str) -> bool {
+        match self
+            .overrides
+            .get(model)
+            .and_then(|m| m.severities.as_ref())
+        {
+            Some(severities) => severities.iter().any(|s| s == severity),
+            None => true,
+        }
+    }
+}
+
+pub fn read_config(profile: Option<&str>) -> Result<Config, String> {
     let toml = match fs::read_to_string("./config.toml") {
         Ok(toml) => toml,
         Err(_e) => return Err("Config file missing".to_owned()),
     };
-    let config: Config = match toml::from_str(&toml) {
+    let mut config: Config = match toml::from_str(&toml) {
         Ok(c) => c,
         Err(_e) => return Err("Config file could not be parsed".to_owned()),
     };
 
+    if let Some(profile) = profile {
+        config.use_profile(profile)?;
+    }
     let models_path = Path::new(&config.modelpath);
-    if !models_path.exists() {
+    if !config.needs_profile() && !models_path.exists() {
         return Err("Base model path in config doesn't exist".to_owned());
     }
+    if config.themes.iter().any(|t| t.name.is_empty()) {
+        return Err("Theme in config is missing a name".to_owned());
+    }
+    Linter::new(&config.lint)?;
+    KeyBindings::new(&config)?;
     Ok(config)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn profiles_replace_model_paths() {
+        let dir = env::temp_dir().to_string_lossy().into_owned();
+        let toml = format!(
+            "modelpath = 'C:/Packages'
+models = ['A', 'B']
+
+[model.A]
+modelpath = 'C:/Customer'
+severities = ['Error']
+
+[profile.build]
+modelpath = '{dir}'
+models = ['A', 'B']
+
+[profile.build.model.B]
+modelpath = 'D:/Drop'
+"
+        );
+        let mut config: Config = toml::from_str(&toml).unwrap();
+        assert_eq!(config.modelpath("A"), "C:/Customer");
+        assert_eq!(config.modelpath("B"), "C:/Packages");
+
+        config.use_profile("build").unwrap();
+        assert_eq!(config.modelpath("A"), dir);
+        assert_eq!(config.modelpath("B"), "D:/Drop");
+        // Only the model path depends on the environment.
+        assert!(!config.shows_severity("A", "Warning"));
+        assert!(config.use_profile("missing").is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use std::{env, path::Path, process};
+
+use crate::{config::Config, resolve::Location};
+
+/// The editor command from the config, `$VISUAL` or `$EDITOR`.
+fn editor_command(config: &Config) -> String {
+    if let Some(editor) = &config.editor {
+        return editor.clone();
+    }
+    env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| match cfg!(windows) {
+            true => "notepad".to_owned(),
+            false => "vi".to_owned(),
+        })
+}
+
+/// Splits the command into program and arguments. Quotes group parts with
+/// spaces, e.g. `"C:\Program Files\Notepad++\notepad++.exe" -n{line}`, and
+/// a command that is an existing file is taken as the program as a whole.
+fn split_command(command: &str) -> Vec<String> {
+    let command = command.trim();
+    if Path::new(command).is_file() {
+        return vec![command.to_owned()];
+    }
+    let mut parts = vec![];
+    let mut part = String::new();
+    let mut quote = None;
+    let mut in_part = false;
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => part.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_part = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_part {
+                    parts.push(std::mem::take(&mut part));
+                    in_part = false;
+                }
+            }
+            (None, c) => {
+                part.push(c);
+                in_part = true;
+            }
+        }
+    }
+    if in_part {
+        parts.push(part);
+    }
+    parts
+}
+
+/// Opens the file in the editor and waits for it to exit. `{file}` and
+/// `{line}` in the command are replaced, otherwise the file is appended.
+pub fn open_in_editor(config: &Config, location: &Location) -> Result<(), String> {
+    let command = editor_command(config);
+    let file = location.file.to_string_lossy();
+    let line = location.line.to_string();
+    let mut parts = split_command(&command).into_iter();
+    let Some(program) = parts.next() else {
+        return Err("No editor configured".to_owned());
+    };
+    let mut args: Vec<String> = parts
+        .map(|a| a.replace("{file}", &file).replace("{line}", &line))
+        .collect();
+    if !command.contains("{file}") {
+        args.push(file.into_owned());
+    }
+    match process::Command::new(&program).args(&args).status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("Editor exited with {status}")),
+        Err(_) => Err(format!("Could not start editor {program}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_on_whitespace() {
+        assert_eq!(
+            split_command("code -g {file}:{line}"),
+            ["code", "-g", "{file}:{line}"]
+        );
+    }
+
+    #[test]
+    fn keeps_quoted_program_paths() {
+        assert_eq!(
+            split_command(r#""C:\Program Files\Notepad++\notepad++.exe" -n{line} {file}"#),
+            [
+                r"C:\Program Files\Notepad++\notepad++.exe",
+                "-n{line}",
+                "{file}"
+            ]
+        );
+        assert_eq!(split_command("vim '+{line}' ''"), ["vim", "+{line}", ""]);
+    }
+
+    #[test]
+    fn takes_an_existing_file_as_the_program() {
+        let dir = env::temp_dir().join(format!("bp-manager editor {}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let program = dir.join("my editor.exe");
+        std::fs::write(&program, "").unwrap();
+        let command = program.to_string_lossy().into_owned();
+        assert_eq!(split_command(&command), vec![command]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt::Write;
+
+use quick_xml::escape::escape;
+
+use crate::{json::Stats, report::Report};
+
+const STYLE: &str = "body { font-family: Segoe UI, sans-serif; margin: 2em; color: #0f172a; }
+table { border-collapse: collapse; margin-bottom: 2em; width: 100%; }
+th, td { border: 1px solid #cbd5e1; padding: 4px 8px; text-align: left; vertical-align: top; }
+th { background: #1e3a8a; color: #e2e8f0; }
+tr:nth-child(even) td { background: #f1f5f9; }
+.new { color: #b91c1c; font-weight: bold; }
+.suppressed { color: #047857; }
+.summary td:last-child { text-align: right; }";
+
+/// Renders the report as a self-contained html page.
+pub fn to_html(report: &Report) -> Result<String, String> {
+    let stats = Stats::new(report);
+    let model = escape(&report.model);
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Best practices: {model}</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>\n<h1>Best practices: {model}</h1>\n"
+    );
+
+    html.push_str("<h2>Summary</h2>\n<table class=\"summary\">\n");
+    for (name, count) in [
+        ("Diagnostics", stats.Diagnostics),
+        ("Suppressed", stats.Suppressed),
+        ("New", stats.Unsuppressed),
+        ("Suppressions in file", stats.Suppressions),
+    ] {
+        let _ = writeln!(html, "<tr><td>{name}</td><td>{count}</td></tr>");
+    }
+    for (severity, count) in &stats.BySeverity {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{count}</td></tr>",
+            escape(severity)
+        );
+    }
+    html.push_str("</table>\n");
+
+    for moniker in report.monikers() {
+        let _ = writeln!(
+            html,
+            "<h2>{} ({})</h2>",
+            escape(moniker),
+            stats.ByMoniker.get(moniker).unwrap_or(&0)
+        );
+        if let Some(info) = report.catalogue.get(moniker) {
+            let _ = writeln!(
+                html,
+                "<p><b>{}</b>. {}<br>Fix: {}</p>",
+                escape(&info.title),
+                escape(&info.explanation),
+                escape(&info.fix)
+            );
+        }
+        html.push_str("<table>\n<tr><th>Severity</th><th>Path</th><th>File</th><th>Message</th><th>Status</th><th>Justification</th></tr>\n");
+        for d in report.diagnostics.iter().filter(|d| d.Moniker == moniker) {
+            let status = match report.suppression(d) {
+                Some(_) => "<span class=\"suppressed\">Suppressed</span>",
+                None => "<span class=\"new\">New</span>",
+            };
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{status}</td><td>{}</td></tr>",
+                escape(&d.Severity),
+                escape(&d.Path),
+                escape(
+                    &report
+                        .location(d)
+                        .map(|l| l.short(&report.metadata_dir))
+                        .unwrap_or_default()
+                ),
+                escape(&d.Message),
+                escape(report.justification(d).unwrap_or(""))
+            );
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body>\n</html>");
+    Ok(html)
+}
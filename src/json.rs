@@ -0,0 +1,103 @@
+#![allow(non_snake_case)]
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{read::Diagnostic, report::Report};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum JsonFormat {
+    /// A single pretty printed json document.
+    Json,
+    /// One compact json object per line.
+    JsonLines,
+}
+
+/// Json view of a `Diagnostic`. Unlike the xml serialization, which has to
+/// match the suppression file, this includes every field.
+#[derive(Serialize)]
+pub struct JsonDiagnostic<'a> {
+    pub Moniker: &'a str,
+    pub Severity: &'a str,
+    pub DiagnosticType: &'a str,
+    pub ElementType: &'a str,
+    pub Path: &'a str,
+    pub Message: &'a str,
+    pub Justification: &'a str,
+}
+
+impl<'a> From<&'a Diagnostic> for JsonDiagnostic<'a> {
+    fn from(d: &'a Diagnostic) -> Self {
+        Self {
+            Moniker: &d.Moniker,
+            Severity: &d.Severity,
+            DiagnosticType: &d.DiagnosticType,
+            ElementType: &d.ElementType,
+            Path: &d.Path,
+            Message: &d.Message,
+            Justification: &d.Justification,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Stats {
+    pub Model: String,
+    pub Diagnostics: usize,
+    pub Suppressed: usize,
+    pub Unsuppressed: usize,
+    pub Suppressions: usize,
+    pub BySeverity: BTreeMap<String, usize>,
+    pub ByMoniker: BTreeMap<String, usize>,
+}
+
+impl Stats {
+    pub fn new(report: &Report) -> Self {
+        let mut by_severity = BTreeMap::new();
+        let mut by_moniker = BTreeMap::new();
+        for d in &report.diagnostics {
+            *by_severity.entry(d.Severity.clone()).or_insert(0) += 1;
+            *by_moniker.entry(d.Moniker.clone()).or_insert(0) += 1;
+        }
+        let suppressed = report
+            .diagnostics
+            .iter()
+            .filter(|d| report.suppression(d).is_some())
+            .count();
+        Self {
+            Model: report.model.clone(),
+            Diagnostics: report.diagnostics.len(),
+            Suppressed: suppressed,
+            Unsuppressed: report.diagnostics.len() - suppressed,
+            Suppressions: report.suppressions.len(),
+            BySeverity: by_severity,
+            ByMoniker: by_moniker,
+        }
+    }
+}
+
+pub fn diagnostics_to_json(data: &[Diagnostic], format: JsonFormat) -> Result<String, String> {
+    let rows: Vec<JsonDiagnostic> = data.iter().map(JsonDiagnostic::from).collect();
+    to_json(&rows, format)
+}
+
+pub fn to_json<T: Serialize>(rows: &[T], format: JsonFormat) -> Result<String, String> {
+    let result = match format {
+        JsonFormat::Json => serde_json::to_string_pretty(rows),
+        JsonFormat::JsonLines => rows
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<Vec<_>, _>>()
+            .map(|lines| lines.join("\n")),
+    };
+    result.map_err(|_| "Could not serialize json".to_owned())
+}
+
+pub fn value_to_json<T: Serialize>(value: &T, format: JsonFormat) -> Result<String, String> {
+    let result = match format {
+        JsonFormat::Json => serde_json::to_string_pretty(value),
+        JsonFormat::JsonLines => serde_json::to_string(value),
+    };
+    result.map_err(|_| "Could not serialize json".to_owned())
+}
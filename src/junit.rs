@@ -0,0 +1,75 @@
+use std::fmt::Write;
+
+use quick_xml::escape::escape;
+
+use crate::{read::Diagnostic, report::Report};
+
+fn is_test_case(d: &Diagnostic) -> bool {
+    d.Severity == "Error" || d.Severity == "Warning"
+}
+
+/// Converts the report into a JUnit xml report: each moniker is a test suite
+/// and each error or warning a test case. Unjustified diagnostics fail,
+/// justified ones are skipped with the justification as message.
+pub fn to_junit(report: &Report) -> Result<String, String> {
+    let mut suites = String::new();
+    let (mut total_tests, mut total_failures, mut total_skipped) = (0, 0, 0);
+    for moniker in report.monikers() {
+        let cases: Vec<&Diagnostic> = report
+            .diagnostics
+            .iter()
+            .filter(|d| d.Moniker == moniker && is_test_case(d))
+            .collect();
+        if cases.is_empty() {
+            continue;
+        }
+        let mut body = String::new();
+        let (mut failures, mut skipped) = (0, 0);
+        for d in &cases {
+            let _ = write!(
+                body,
+                "    <testcase classname=\"{}\" name=\"{}\">",
+                escape(moniker),
+                escape(&d.Path)
+            );
+            match report.justification(d) {
+                Some(justification) => {
+                    skipped += 1;
+                    let _ = write!(body, "<skipped message=\"{}\"/>", escape(justification));
+                }
+                None => {
+                    failures += 1;
+                    let _ = write!(
+                        body,
+                        "<failure type=\"{}\" message=\"{}\">{}</failure>",
+                        escape(&d.Severity),
+                        escape(&d.Message),
+                        escape(&d.Path)
+                    );
+                }
+            }
+            body.push_str("</testcase>\n");
+        }
+        let _ = writeln!(
+            suites,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\">",
+            escape(moniker),
+            cases.len(),
+            failures,
+            skipped
+        );
+        suites.push_str(&body);
+        suites.push_str("  </testsuite>\n");
+        total_tests += cases.len();
+        total_failures += failures;
+        total_skipped += skipped;
+    }
+    Ok(format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\">\n{}</testsuites>",
+        escape(&report.model),
+        total_tests,
+        total_failures,
+        total_skipped,
+        suites
+    ))
+}
@@ -0,0 +1,341 @@
+use crossterm::event::KeyCode;
+use serde::Deserialize;
+
+use crate::config::Config;
+
+/// What a key does. Set per mode in the `[keys.<mode>]` sections of the
+/// config, e.g. `down = ['n', 'Down']`.
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Quit,
+    Up,
+    Down,
+    NextColor,
+    PreviousColor,
+    Justify,
+    Write,
+    WriteForce,
+    WorkItem,
+    Expiry,
+    Owner,
+    Source,
+    Editor,
+    RuleInfo,
+    ExportCsv,
+    ImportCsv,
+    Report,
+    Propose,
+    Review,
+    SwitchModel,
+    Back,
+    Snippets,
+    Insert,
+    Save,
+    Cancel,
+    Approve,
+    ApproveAll,
+    Reject,
+    Select,
+    Profiles,
+}
+
+struct Binding {
+    mode: &'static str,
+    action: Action,
+    keys: Vec<KeyCode>,
+    /// Shown in the footer, unless `None`.
+    label: Option<&'static str>,
+}
+
+const MODES: [&str; 9] = [
+    "normal",
+    "justification",
+    "snippet",
+    "work-item",
+    "expiry",
+    "review",
+    "error",
+    "model-select",
+    "profile-select",
+];
+
+/// Default bindings in footer order.
+fn defaults(config: &Config) -> Vec<Binding> {
+    use Action::*;
+    use KeyCode::{Char, Enter, Esc, Left, Right, Tab};
+    let mut defaults = vec![
+        ("normal", Justify, vec![Enter], Some("enter justification")),
+        ("normal", Quit, vec![Char('q')], Some("quit")),
+        ("normal", Up, vec![KeyCode::Up, Char('k')], Some("move up")),
+        (
+            "normal",
+            Down,
+            vec![KeyCode::Down, Char('j')],
+            Some("move down"),
+        ),
+        ("normal", NextColor, vec![Right, Char('l')], None),
+        ("normal", PreviousColor, vec![Left, Char('h')], None),
+        ("normal", Write, vec![Char('w')], Some("write file")),
+        (
+            "normal",
+            WriteForce,
+            vec![Char('W')],
+            Some("write ignoring lint"),
+        ),
+        ("normal", WorkItem, vec![Char('t')], Some("work item")),
+        ("normal", Expiry, vec![Char('x')], Some("expiry")),
+        ("normal", Owner, vec![Char('o')], Some("filter owner")),
+        ("normal", Source, vec![Char('s')], Some("source")),
+        ("normal", Editor, vec![Char('E')], Some("open in editor")),
+        ("normal", RuleInfo, vec![Char('?')], Some("rule info")),
+        ("normal", ExportCsv, vec![Char('e')], Some("export csv")),
+        ("normal", ImportCsv, vec![Char('i')], Some("import csv")),
+        ("normal", Report, vec![Char('r')], Some("html report")),
+        ("justification", Back, vec![Enter, Esc], Some("go back")),
+        (
+            "justification",
+            Snippets,
+            vec![Tab],
+            Some("snippets and suggestions"),
+        ),
+        ("snippet", Insert, vec![Enter], Some("insert")),
+        ("snippet", Up, vec![KeyCode::Up, Char('k')], Some("move up")),
+        (
+            "snippet",
+            Down,
+            vec![KeyCode::Down, Char('j')],
+            Some("move down"),
+        ),
+        ("snippet", Cancel, vec![Esc], Some("cancel")),
+        ("work-item", Back, vec![Enter, Esc], Some("go back")),
+        (
+            "expiry",
+            Save,
+            vec![Enter],
+            Some("save expiry date (YYYY-MM-DD, empty to clear)"),
+        ),
+        ("expiry", Cancel, vec![Esc], Some("cancel")),
+        ("review", Approve, vec![Char('a')], Some("approve")),
+        ("review", ApproveAll, vec![Char('A')], Some("approve all")),
+        ("review", Reject, vec![Char('n')], Some("reject")),
+        ("review", Up, vec![KeyCode::Up, Char('k')], Some("move up")),
+        (
+            "review",
+            Down,
+            vec![KeyCode::Down, Char('j')],
+            Some("move down"),
+        ),
+        ("review", Back, vec![Esc, Char('v')], Some("back")),
+        ("error", Quit, vec![Esc, Char('q')], Some("quit")),
+        ("model-select", Select, vec![Enter], Some("select")),
+        ("model-select", Up, vec![KeyCode::Up, Char('k')], None),
+        ("model-select", Down, vec![KeyCode::Down, Char('j')], None),
+        ("model-select", NextColor, vec![Right, Char('l')], None),
+        ("model-select", PreviousColor, vec![Left, Char('h')], None),
+        ("profile-select", Select, vec![Enter], Some("select")),
+        ("profile-select", Up, vec![KeyCode::Up, Char('k')], None),
+        ("profile-select", Down, vec![KeyCode::Down, Char('j')], None),
+        ("profile-select", NextColor, vec![Right, Char('l')], None),
+        ("profile-select", PreviousColor, vec![Left, Char('h')], None),
+        ("profile-select", Quit, vec![Esc, Char('q')], Some("quit")),
+    ];
+    // Keys for features that are switched off aren't bound or shown.
+    if config.workflow {
+        defaults.push(("normal", Propose, vec![Char('p')], Some("propose")));
+        defaults.push(("normal", Review, vec![Char('v')], Some("review")));
+    }
+    defaults.push((
+        "normal",
+        SwitchModel,
+        vec![Esc, Char('m')],
+        Some("switch model"),
+    ));
+    if !config.profiles.is_empty() {
+        defaults.push(("model-select", Profiles, vec![Char('p')], Some("profiles")));
+    }
+    defaults.push(("model-select", Quit, vec![Esc, Char('q')], Some("quit")));
+    defaults
+        .into_iter()
+        .map(|(mode, action, keys, label)| Binding {
+            mode,
+            action,
+            keys,
+            label,
+        })
+        .collect()
+}
+
+/// Parses `q`, `Space`, `Enter`, `Down`, `F5`, ...
+fn parse_key(key: &str) -> Result<KeyCode, String> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+    let code = match key {
+        "Space" => KeyCode::Char(' '),
+        "Enter" => KeyCode::Enter,
+        "Esc" => KeyCode::Esc,
+        "Tab" => KeyCode::Tab,
+        "Backspace" => KeyCode::Backspace,
+        "Delete" => KeyCode::Delete,
+        "Up" => KeyCode::Up,
+        "Down" => KeyCode::Down,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        "PageUp" => KeyCode::PageUp,
+        "PageDown" => KeyCode::PageDown,
+        k => match k.strip_prefix('F').and_then(|n| n.parse().ok()) {
+            Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+            _ => return Err(format!("Unknown key in config: {key}")),
+        },
+    };
+    Ok(code)
+}
+
+fn key_name(key: &KeyCode) -> String {
+    match key {
+        KeyCode::Up => "↑".to_owned(),
+        KeyCode::Down => "↓".to_owned(),
+        KeyCode::Left => "←".to_owned(),
+        KeyCode::Right => "→".to_owned(),
+        KeyCode::Char(' ') => "Space".to_owned(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{n}"),
+        k => format!("{k:?}"),
+    }
+}
+
+/// The active key bindings: the defaults with the `[keys]` section of the
+/// config applied.
+pub struct KeyBindings {
+    bindings: Vec<Binding>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: defaults(&Config::default()),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn new(config: &Config) -> Result<Self, String> {
+        let mut bindings = defaults(config);
+        for (mode, actions) in &config.keys {
+            let Some(mode) = MODES.iter().find(|m| *m == mode) else {
+                return Err(format!("Unknown mode in [keys] in config: {mode}"));
+            };
+            for (name, keys) in actions {
+                let Ok(action) = toml::Value::String(name.clone()).try_into::<Action>() else {
+                    return Err(format!("Unknown action in [keys] in config: {name}"));
+                };
+                let keys = keys
+                    .iter()
+                    .map(|k| parse_key(k))
+                    .collect::<Result<Vec<_>, _>>()?;
+                // A remapped key no longer does what it did before.
+                for binding in bindings.iter_mut().filter(|b| b.mode == *mode) {
+                    binding.keys.retain(|k| !keys.contains(k));
+                }
+                match bindings
+                    .iter_mut()
+                    .find(|b| b.mode == *mode && b.action == action)
+                {
+                    Some(binding) => binding.keys = keys,
+                    None => return Err(format!("Action {name} can't be bound in {mode} mode")),
+                }
+            }
+        }
+        Ok(Self { bindings })
+    }
+
+    pub fn action(&self, mode: &str, key: KeyCode) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|b| b.mode == mode && b.keys.contains(&key))
+            .map(|b| b.action)
+    }
+
+    /// Footer text listing the first key of each labelled binding.
+    pub fn help(&self, mode: &str) -> String {
+        self.bindings
+            .iter()
+            .filter(|b| b.mode == mode)
+            .filter_map(|b| Some(format!("({}) {}", key_name(b.keys.first()?), b.label?)))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(mode: &str, action: &str, keys: &[&str]) -> Result<KeyBindings, String> {
+        let mut config = Config::default();
+        config.keys.insert(
+            mode.to_owned(),
+            [(
+                action.to_owned(),
+                keys.iter().map(|k| k.to_string()).collect(),
+            )]
+            .into(),
+        );
+        KeyBindings::new(&config)
+    }
+
+    #[test]
+    fn parses_keys() {
+        assert_eq!(parse_key("q"), Ok(KeyCode::Char('q')));
+        assert_eq!(parse_key("Q"), Ok(KeyCode::Char('Q')));
+        assert_eq!(parse_key("Space"), Ok(KeyCode::Char(' ')));
+        assert_eq!(parse_key("PageDown"), Ok(KeyCode::PageDown));
+        assert_eq!(parse_key("F1"), Ok(KeyCode::F(1)));
+        assert_eq!(parse_key("F12"), Ok(KeyCode::F(12)));
+        for key in ["", "F0", "F13", "Fx", "space", "Ctrl+c"] {
+            assert_eq!(parse_key(key), Err(format!("Unknown key in config: {key}")));
+        }
+    }
+
+    #[test]
+    fn remaps_keys() {
+        let keys = bindings("normal", "down", &["n"]).unwrap();
+        assert!(keys.action("normal", KeyCode::Char('n')) == Some(Action::Down));
+        assert!(keys.action("normal", KeyCode::Char('j')).is_none());
+        assert!(keys.action("normal", KeyCode::Down).is_none());
+        // Other modes keep their defaults.
+        assert!(keys.action("review", KeyCode::Char('j')) == Some(Action::Down));
+    }
+
+    #[test]
+    fn takes_a_remapped_key_from_other_actions() {
+        let keys = bindings("normal", "quit", &["w"]).unwrap();
+        assert!(keys.action("normal", KeyCode::Char('w')) == Some(Action::Quit));
+        assert!(keys.action("normal", KeyCode::Char('q')).is_none());
+        assert!(!keys.help("normal").contains("write file"));
+    }
+
+    #[test]
+    fn rejects_invalid_bindings() {
+        assert_eq!(
+            bindings("nromal", "down", &["n"]).err(),
+            Some("Unknown mode in [keys] in config: nromal".to_owned())
+        );
+        assert_eq!(
+            bindings("normal", "dwon", &["n"]).err(),
+            Some("Unknown action in [keys] in config: dwon".to_owned())
+        );
+        assert_eq!(
+            bindings("normal", "approve", &["a"]).err(),
+            Some("Action approve can't be bound in normal mode".to_owned())
+        );
+        assert_eq!(
+            bindings("normal", "down", &["Ctrl+n"]).err(),
+            Some("Unknown key in config: Ctrl+n".to_owned())
+        );
+    }
+}
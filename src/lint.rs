@@ -0,0 +1,182 @@
+use regex::{escape, Regex};
+use serde::Deserialize;
+
+use crate::read::Diagnostic;
+
+/// The `[lint]` section of the config. Nothing is checked by default.
+#[derive(Deserialize, Default)]
+pub struct LintConfig {
+    pub min_length: Option<usize>,
+    #[serde(default)]
+    pub banned: Vec<String>,
+    pub ticket: Option<String>,
+}
+
+#[derive(Default)]
+pub struct Linter {
+    min_length: usize,
+    banned: Vec<(String, Regex)>,
+    ticket: Option<Regex>,
+}
+
+impl Linter {
+    pub fn new(config: &LintConfig) -> Result<Self, String> {
+        let mut banned = vec![];
+        for phrase in &config.banned {
+            // Whole words only, but a phrase starting or ending with
+            // punctuation, like `...`, may follow or precede a word.
+            let boundary = |c: Option<char>| match c {
+                Some(c) if c.is_alphanumeric() || c == '_' => r"\b",
+                _ => "",
+            };
+            let start = boundary(phrase.chars().next());
+            let end = boundary(phrase.chars().last());
+            match Regex::new(&format!(r"(?i){start}{}{end}", escape(phrase))) {
+                Ok(r) if !phrase.is_empty() => banned.push((phrase.clone(), r)),
+                _ => return Err(format!("Invalid banned phrase in config: {phrase}")),
+            }
+        }
+        let ticket = match &config.ticket {
+            Some(t) => match Regex::new(t) {
+                Ok(r) => Some(r),
+                Err(_) => return Err("Ticket regex in config is invalid".to_owned()),
+            },
+            None => None,
+        };
+        Ok(Self {
+            min_length: config.min_length.unwrap_or(0),
+            banned,
+            ticket,
+        })
+    }
+
+    /// Problems with a justification. Empty justifications aren't written,
+    /// so they have none.
+    pub fn check(&self, justification: &str) -> Vec<String> {
+        let justification = justification.trim();
+        let mut problems = vec![];
+        if justification.is_empty() {
+            return problems;
+        }
+        if justification.chars().count() < self.min_length {
+            problems.push(format!("shorter than {} characters", self.min_length));
+        }
+        for (phrase, r) in &self.banned {
+            if r.is_match(justification) {
+                problems.push(format!("contains \"{phrase}\""));
+            }
+        }
+        if let Some(ticket) = &self.ticket {
+            if !ticket.is_match(justification) {
+                problems.push("missing ticket reference".to_owned());
+            }
+        }
+        problems
+    }
+
+    /// One line per problem, for all diagnostics.
+    pub fn check_all(&self, data: &[Diagnostic]) -> Vec<String> {
+        let mut lines = vec![];
+        for d in data {
+            for problem in self.check(&d.Justification) {
+                lines.push(format!("{} {}: {problem}", d.Moniker, d.Path));
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linter(min_length: Option<usize>, banned: &[&str], ticket: Option<&str>) -> Linter {
+        Linter::new(&LintConfig {
+            min_length,
+            banned: banned.iter().map(|b| b.to_string()).collect(),
+            ticket: ticket.map(|t| t.to_owned()),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn checks_nothing_by_default() {
+        assert!(Linter::default().check("ok").is_empty());
+        assert!(linter(Some(10), &["ok"], Some(r"#\d+"))
+            .check("  ")
+            .is_empty());
+    }
+
+    #[test]
+    fn checks_the_length_in_characters() {
+        let linter = linter(Some(5), &[], None);
+        assert_eq!(linter.check("abcd"), ["shorter than 5 characters"]);
+        assert!(linter.check("abcde").is_empty());
+        assert!(linter.check("  æøåäö  ").is_empty());
+        assert_eq!(linter.check(" æøå "), ["shorter than 5 characters"]);
+    }
+
+    #[test]
+    fn matches_banned_phrases_as_whole_words() {
+        let linter = linter(None, &["ok", "n/a", "...", "to do"], None);
+        assert_eq!(linter.check("OK"), ["contains \"ok\""]);
+        assert_eq!(linter.check("It's ok."), ["contains \"ok\""]);
+        assert_eq!(linter.check("(ok)"), ["contains \"ok\""]);
+        assert!(linter.check("Token is checked by the caller").is_empty());
+        assert!(linter.check("Booking").is_empty());
+        assert_eq!(linter.check("N/A"), ["contains \"n/a\""]);
+        assert!(linter.check("Ann/Anderson approved").is_empty());
+        assert_eq!(linter.check("wait..."), ["contains \"...\""]);
+        assert!(linter.check("Still to  do").is_empty());
+        assert_eq!(linter.check("Still TO DO"), ["contains \"to do\""]);
+        assert_eq!(
+            linter.check("ok, n/a"),
+            ["contains \"ok\"", "contains \"n/a\""]
+        );
+    }
+
+    #[test]
+    fn requires_a_ticket_reference() {
+        let linter = linter(None, &[], Some(r"#\d+"));
+        assert_eq!(linter.check("Approved"), ["missing ticket reference"]);
+        assert!(linter.check("Approved in #123").is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        let config = |banned: &str, ticket: Option<&str>| LintConfig {
+            min_length: None,
+            banned: vec![banned.to_owned()],
+            ticket: ticket.map(|t| t.to_owned()),
+        };
+        assert_eq!(
+            Linter::new(&config("", None)).err(),
+            Some("Invalid banned phrase in config: ".to_owned())
+        );
+        assert_eq!(
+            Linter::new(&config("ok", Some("(#"))).err(),
+            Some("Ticket regex in config is invalid".to_owned())
+        );
+    }
+
+    #[test]
+    fn lists_problems_per_diagnostic() {
+        let linter = linter(Some(5), &["ok"], None);
+        let data = [Diagnostic {
+            DiagnosticType: "BestPractices".to_owned(),
+            Severity: "Warning".to_owned(),
+            ElementType: "Class".to_owned(),
+            Path: "dynamics://Class/A".to_owned(),
+            Moniker: "BPUnusedMethod".to_owned(),
+            Message: String::new(),
+            Justification: "ok".to_owned(),
+        }];
+        assert_eq!(
+            linter.check_all(&data),
+            [
+                "BPUnusedMethod dynamics://Class/A: shorter than 5 characters",
+                "BPUnusedMethod dynamics://Class/A: contains \"ok\""
+            ]
+        );
+    }
+}
@@ -1,6 +1,6 @@
 #![allow(clippy::enum_glob_use, clippy::wildcard_imports)]
 
-use std::{cmp, error::Error, io};
+use std::{cmp, collections::HashSet, env, error::Error, fs, io, path::PathBuf, process, slice};
 
 use config::{read_config, Config};
 use crossterm::{
@@ -9,54 +9,56 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{prelude::*, widgets::*};
-use style::palette::tailwind;
 
+mod annotations;
 mod read;
-use read::{read_diagnostics, Diagnostic};
+use read::{read_actionable_diagnostics, Diagnostic};
 mod write;
-use write::write_diagnostics;
+use write::{read_suppressions, write_diagnostics};
+mod catalogue;
+use catalogue::Catalogue;
+mod check;
+mod cli;
 mod config;
-
-const PALETTES: [tailwind::Palette; 4] = [
-    tailwind::BLUE,
-    tailwind::EMERALD,
-    tailwind::INDIGO,
-    tailwind::RED,
-];
-const INFO_TEXT_NORMAL: &str =
-    "(Enter) enter justification | (q) quit | (↑) move up | (↓) move down | (w) write file | (Esc) switch model";
-const INFO_TEXT_JUSTIFICATION: &str = "(Enter) go back";
-const INFO_TEXT_ERROR: &str = "(Esc) quit";
-const INFO_TEXT_MODELSELECT: &str = "(Enter) select | (Esc) quit";
+use cli::{parse_args, Command, USAGE};
+mod editor;
+use editor::open_in_editor;
+mod html;
+mod json;
+mod keys;
+use keys::{Action, KeyBindings};
+mod lint;
+mod meta;
+use lint::Linter;
+use meta::{
+    apply_drafts, read_meta, save_drafts, set_expiry, set_state, ReviewState, SuppressionMeta,
+};
+mod junit;
+mod markdown;
+mod owners;
+use owners::Owners;
+mod policy;
+use policy::{clear_must_fix, Policy};
+mod report;
+mod resolve;
+mod rules;
+use report::{render_report, Report, ReportFormat};
+use resolve::{metadata_dir, resolve, Location};
+mod sarif;
+mod snippets;
+mod source;
+use source::{method_name, method_offset, read_source, SourceBlock};
+mod spreadsheet;
+mod state;
+mod suggestions;
+mod theme;
+use spreadsheet::{export_csv, import_csv};
+use state::{load_state, save_state, State};
+use suggestions::{collect_suggestions, Suggestions};
+use theme::{themes, Theme};
 
 const ITEM_HEIGHT: usize = 4;
 
-struct TableColors {
-    buffer_bg: Color,
-    header_bg: Color,
-    header_fg: Color,
-    row_fg: Color,
-    selected_style_fg: Color,
-    normal_row_color: Color,
-    alt_row_color: Color,
-    footer_border_color: Color,
-}
-
-impl TableColors {
-    const fn new(color: &tailwind::Palette) -> Self {
-        Self {
-            buffer_bg: tailwind::SLATE.c950,
-            header_bg: color.c900,
-            header_fg: tailwind::SLATE.c200,
-            row_fg: tailwind::SLATE.c200,
-            selected_style_fg: color.c400,
-            normal_row_color: tailwind::SLATE.c950,
-            alt_row_color: tailwind::SLATE.c900,
-            footer_border_color: color.c400,
-        }
-    }
-}
-
 impl Diagnostic {
     const fn ref_array(&self) -> [&String; 3] {
         [&self.Moniker, &self.Severity, &self.Path]
@@ -70,38 +72,129 @@ impl Diagnostic {
 enum InputMode {
     Normal,
     Justification,
+    Snippet,
+    WorkItem,
+    Expiry,
+    Review,
     ModelSelect,
+    ProfileSelect,
     Error,
 }
 
+impl InputMode {
+    /// Name of the mode in the `[keys]` section of the config.
+    fn name(&self) -> &'static str {
+        match self {
+            InputMode::Normal => "normal",
+            InputMode::Justification => "justification",
+            InputMode::Snippet => "snippet",
+            InputMode::WorkItem => "work-item",
+            InputMode::Expiry => "expiry",
+            InputMode::Review => "review",
+            InputMode::ModelSelect => "model-select",
+            InputMode::ProfileSelect => "profile-select",
+            InputMode::Error => "error",
+        }
+    }
+}
+
+/// The metadata file of the selected diagnostic and, once the source pane
+/// has been shown, its source. Kept until another diagnostic is selected, so
+/// redraws don't resolve and read the file again.
+struct SourceCache {
+    model: String,
+    path: String,
+    location: Option<Location>,
+    blocks: Option<Result<Vec<SourceBlock>, String>>,
+}
+
 struct App {
     state: TableState,
+    snippet_state: TableState,
+    suggestions: Suggestions,
     items: Vec<Diagnostic>,
     scroll_state: ScrollbarState,
-    colors: TableColors,
+    /// Justifications of `items` as loaded or last written, to tell unsaved
+    /// ones apart.
+    loaded: Vec<String>,
+    /// The state file as last loaded or saved. `None` if it couldn't be
+    /// read, so it isn't overwritten.
+    session: Option<State>,
+    colors: Theme,
+    themes: Vec<Theme>,
     color_index: usize,
     mode: InputMode,
     model: String,
     config: Config,
+    linter: Linter,
+    keys: KeyBindings,
+    meta: Vec<SuppressionMeta>,
+    /// Indices into `items` of the rows shown, see `update_rows`.
+    rows: Vec<usize>,
+    owners: Owners,
+    owner_filter: Option<String>,
+    show_source: bool,
+    /// Metadata file and source of the selected diagnostic.
+    source: Option<SourceCache>,
+    show_rule_info: bool,
+    catalogue: Catalogue,
+    work_item: String,
+    expiry: String,
     error_message: String,
+    status: String,
 }
 
 impl App {
     fn new(data_vec: Vec<Diagnostic>, config: Config, model: String) -> Self {
+        let themes = themes(&config);
+        let (session, status) = match load_state() {
+            Ok(state) => (Some(state), String::new()),
+            Err(e) => (None, e),
+        };
+        // The theme chosen last time, otherwise the one from the config.
+        let theme = session
+            .as_ref()
+            .and_then(|s| s.theme.clone())
+            .or_else(|| config.theme.clone());
+        let color_index = themes
+            .iter()
+            .position(|t| Some(&t.name) == theme.as_ref())
+            .unwrap_or(0);
         Self {
             state: TableState::default().with_selected(0),
+            snippet_state: TableState::default().with_selected(0),
+            suggestions: Suggestions::new(),
             scroll_state: ScrollbarState::new((cmp::max(data_vec.len(), 1) - 1) * ITEM_HEIGHT),
-            colors: TableColors::new(&PALETTES[0]),
-            color_index: 0,
+            loaded: vec![],
+            session,
+            colors: themes[color_index].clone(),
+            themes,
+            color_index,
             items: data_vec,
             mode: InputMode::ModelSelect,
             model,
+            linter: Linter::new(&config.lint).unwrap_or_default(),
+            keys: KeyBindings::new(&config).unwrap_or_default(),
+            catalogue: Catalogue::new(&config),
             config,
+            meta: vec![],
+            rows: vec![],
+            owners: Owners::default(),
+            owner_filter: None,
+            show_source: false,
+            source: None,
+            show_rule_info: false,
+            work_item: String::new(),
+            expiry: String::new(),
             error_message: String::new(),
+            status,
         }
     }
 
     pub fn next(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i >= count - 1 {
@@ -117,6 +210,9 @@ impl App {
     }
 
     pub fn previous(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
@@ -132,49 +228,351 @@ impl App {
     }
 
     pub fn next_color(&mut self) {
-        self.color_index = (self.color_index + 1) % PALETTES.len();
+        self.set_colors((self.color_index + 1) % self.themes.len());
     }
 
     pub fn previous_color(&mut self) {
-        let count = PALETTES.len();
-        self.color_index = (self.color_index + count - 1) % count;
+        let count = self.themes.len();
+        self.set_colors((self.color_index + count - 1) % count);
+    }
+
+    /// Switches to the theme and remembers it for the next run.
+    pub fn set_colors(&mut self, index: usize) {
+        if index == self.color_index {
+            return;
+        }
+        self.color_index = index;
+        self.colors = self.themes[index].clone();
+        if let Some(mut state) = self.session.clone() {
+            state.theme = Some(self.colors.name.clone());
+            self.store_session(state);
+        }
     }
 
-    pub fn set_colors(&mut self) {
-        self.colors = TableColors::new(&PALETTES[self.color_index]);
+    /// Indices into `items` of the rows shown.
+    pub fn visible(&self) -> &[usize] {
+        &self.rows
+    }
+
+    /// Works out the rows shown, after the items, their metadata, the owner
+    /// filter or the view changed. The review view only shows proposed
+    /// justifications.
+    fn update_rows(&mut self) {
+        let proposed: HashSet<(&str, &str)> = self
+            .meta
+            .iter()
+            .filter(|m| m.state == Some(ReviewState::Proposed))
+            .map(|m| (m.path.as_str(), m.moniker.as_str()))
+            .collect();
+        let review = matches!(self.mode, InputMode::Review);
+        self.rows = (0..self.items.len())
+            .filter(|i| {
+                let d = &self.items[*i];
+                !review || proposed.contains(&(d.Path.as_str(), d.Moniker.as_str()))
+            })
+            .filter(|i| match &self.owner_filter {
+                Some(owner) => self.owners.is_owned_by(&self.items[*i].Path, owner),
+                None => true,
+            })
+            .collect();
+    }
+
+    fn set_meta(&mut self, meta: Vec<SuppressionMeta>) {
+        self.meta = meta;
+        self.update_rows();
+    }
+
+    /// Cycles the owner filter through the owners of the model's
+    /// diagnostics and back to none.
+    pub fn next_owner(&mut self) {
+        let all: Vec<&str> = self
+            .owners
+            .all()
+            .into_iter()
+            .filter(|o| {
+                self.items
+                    .iter()
+                    .any(|d| self.owners.is_owned_by(&d.Path, o))
+            })
+            .collect();
+        let next = match &self.owner_filter {
+            None => all.first(),
+            Some(current) => all
+                .iter()
+                .position(|o| o == current)
+                .and_then(|i| all.get(i + 1)),
+        };
+        self.owner_filter = next.map(|o| o.to_string());
+        self.update_rows();
+        self.state.select(Some(0));
     }
 
     pub fn get_selected(&self) -> Option<&Diagnostic> {
-        if self.items.len() < 1 {
-            return None;
-        }
-        match self.state.selected() {
-            Some(idx) => {
-                if idx >= self.items.len() {
-                    return None;
+        let idx = *self.visible().get(self.state.selected()?)?;
+        self.items.get(idx)
+    }
+
+    pub fn get_selected_mut(&mut self) -> Option<&mut Diagnostic> {
+        let idx = *self.visible().get(self.state.selected()?)?;
+        self.items.get_mut(idx)
+    }
+
+    /// Texts offered for the selected diagnostic: the configured snippets
+    /// followed by justifications used before for the same moniker, with
+    /// their use count.
+    pub fn snippets(&self) -> Vec<(String, Option<usize>)> {
+        let Some(d) = self.get_selected() else {
+            return vec![];
+        };
+        let mut choices: Vec<(String, Option<usize>)> = self
+            .config
+            .snippets
+            .iter()
+            .filter(|s| s.applies_to(d))
+            .map(|s| (s.expand(d), None))
+            .collect();
+        if let Some(used) = self.suggestions.get(&d.Moniker) {
+            for (text, count) in used {
+                if !choices.iter().any(|(t, _)| t == text) {
+                    choices.push((text.clone(), Some(*count)));
                 }
-                Some(&self.items[idx])
             }
-            None => None,
         }
+        choices
     }
 
-    pub fn get_selected_mut(&mut self) -> Option<&mut Diagnostic> {
-        if self.items.len() < 1 {
-            return None;
+    pub fn next_snippet(&mut self) {
+        let count = self.snippets().len();
+        if count > 0 {
+            let i = self.snippet_state.selected().map_or(0, |i| (i + 1) % count);
+            self.snippet_state.select(Some(i));
         }
-        match self.state.selected() {
-            Some(idx) => Some(self.items.get_mut(idx).unwrap()),
+    }
+
+    pub fn previous_snippet(&mut self) {
+        let count = self.snippets().len();
+        if count > 0 {
+            let i = self
+                .snippet_state
+                .selected()
+                .map_or(0, |i| (i + count - 1) % count);
+            self.snippet_state.select(Some(i));
+        }
+    }
+
+    pub fn insert_snippet(&mut self) {
+        let text = match self.snippet_state.selected() {
+            Some(i) => self.snippets().into_iter().nth(i).map(|(text, _)| text),
             None => None,
+        };
+        if let (Some(text), Some(d)) = (text, self.get_selected_mut()) {
+            if !d.Justification.is_empty() && !d.Justification.ends_with(' ') {
+                d.Justification.push(' ');
+            }
+            d.Justification.push_str(&text);
+        }
+    }
+
+    pub fn selected_meta(&self) -> Option<&SuppressionMeta> {
+        let d = self.get_selected()?;
+        self.meta.iter().find(|m| m.is_for(d))
+    }
+
+    /// Must-fix rules can only be fixed, not justified.
+    pub fn edit_justification(&mut self) {
+        match self.get_selected() {
+            Some(d) if Policy::of(&self.config, &self.model, &d.Moniker) == Policy::MustFix => {
+                self.status = format!("{} must be fixed, it can't be justified", d.Moniker)
+            }
+            _ => self.set_mode(InputMode::Justification),
+        }
+    }
+
+    pub fn edit_expiry(&mut self) {
+        self.expiry = self
+            .selected_meta()
+            .and_then(|m| m.expires.clone())
+            .unwrap_or_default();
+        self.set_mode(InputMode::Expiry);
+    }
+
+    pub fn save_expiry(&mut self) {
+        let Some(d) = self.get_selected() else {
+            return;
+        };
+        let expiry = Some(self.expiry.trim()).filter(|e| !e.is_empty());
+        match set_expiry(&self.config, &self.model, d, expiry) {
+            Ok(meta) => self.set_meta(meta),
+            Err(e) => self.status = e,
+        }
+    }
+
+    /// The metadata file and line of a diagnostic.
+    pub fn location(&self, d: &Diagnostic) -> Option<Location> {
+        resolve(&metadata_dir(&self.config, &self.model), &d.Path)
+    }
+
+    /// Resolves the selected diagnostic's metadata file, and reads its source
+    /// if the source pane is shown, unless that was done for it already.
+    pub fn cache_source(&mut self) {
+        let Some(d) = self.get_selected() else {
+            self.source = None;
+            return;
+        };
+        let cached = self
+            .source
+            .as_ref()
+            .is_some_and(|c| c.model == self.model && c.path == d.Path);
+        if !cached {
+            self.source = Some(SourceCache {
+                model: self.model.clone(),
+                path: d.Path.clone(),
+                location: self.location(d),
+                blocks: None,
+            });
+        }
+        if let Some(cache) = self.source.as_mut().filter(|_| self.show_source) {
+            if let (Some(location), None) = (&cache.location, &cache.blocks) {
+                cache.blocks = Some(read_source(&location.file));
+            }
+        }
+    }
+
+    pub fn selected_location(&self) -> Option<&Location> {
+        self.source.as_ref()?.location.as_ref()
+    }
+
+    pub fn set_view(&mut self, mode: InputMode) {
+        self.set_mode(mode);
+        self.state.select(Some(0));
+    }
+
+    pub fn propose(&mut self) {
+        let Some(d) = self.get_selected().cloned() else {
+            return;
+        };
+        let work_item = Some(self.work_item.trim()).filter(|w| !w.is_empty());
+        let result = read_suppressions(&self.config, &self.model)
+            .and_then(|s| {
+                save_drafts(
+                    &self.config,
+                    &self.model,
+                    slice::from_ref(&d),
+                    &s,
+                    work_item,
+                )
+            })
+            .and_then(|_| set_state(&self.config, &self.model, &[&d], ReviewState::Proposed));
+        match result {
+            Ok(meta) => {
+                self.set_meta(meta);
+                // The metadata file has it now, so it isn't unsaved anymore.
+                if let Some(i) = self.items.iter().position(|i| i.same_as(&d)) {
+                    self.loaded[i] = d.Justification.clone();
+                }
+            }
+            Err(e) => self.status = e,
+        }
+    }
+
+    /// Sets the review state of the selected row, or of all shown rows.
+    pub fn review(&mut self, state: ReviewState, all: bool) {
+        let selected: Vec<&Diagnostic> = match all {
+            true => self.visible().iter().map(|i| &self.items[*i]).collect(),
+            false => self.get_selected().into_iter().collect(),
+        };
+        match set_state(&self.config, &self.model, &selected, state) {
+            Ok(meta) => self.set_meta(meta),
+            Err(e) => self.status = e,
+        }
+        let count = self.visible().len();
+        if self.state.selected().is_some_and(|i| i >= count) {
+            self.state.select(Some(count.saturating_sub(1)));
         }
     }
 
     pub fn set_mode(&mut self, mode: InputMode) {
+        let review = matches!(self.mode, InputMode::Review);
         self.mode = mode;
+        if review != matches!(self.mode, InputMode::Review) {
+            self.update_rows();
+        }
+    }
+
+    /// Writes the suppression file, unless justifications fail the lint
+    /// rules and `force` isn't set.
+    fn write_file(&mut self, force: bool) {
+        let problems = self.linter.check_all(&self.items).len();
+        if problems > 0 && !force {
+            self.status =
+                format!("Not written: {problems} justification problems. (W) write anyway");
+            return;
+        }
+        let work_item = Some(self.work_item.trim()).filter(|w| !w.is_empty());
+        if self.config.workflow {
+            let drafts = read_suppressions(&self.config, &self.model)
+                .and_then(|s| save_drafts(&self.config, &self.model, &self.items, &s, work_item));
+            if let Err(e) = drafts {
+                return self.set_error(e);
+            }
+        }
+        match write_diagnostics(&self.items, &self.config, &self.model, work_item) {
+            Ok(_) => {
+                self.set_meta(read_meta(&self.config, &self.model).unwrap_or_default());
+                self.loaded = self.items.iter().map(|d| d.Justification.clone()).collect();
+                self.status = "Suppressions written".to_owned()
+            }
+            Err(e) => self.set_error(e),
+        }
+    }
+
+    fn csv_file(&self) -> PathBuf {
+        PathBuf::from(format!("{}_diagnostics.csv", &self.model))
+    }
+
+    /// Exports the rows currently shown.
+    fn export_csv(&mut self) -> Result<(), String> {
+        let rows: Vec<Diagnostic> = self
+            .visible()
+            .iter()
+            .map(|i| self.items[*i].clone())
+            .collect();
+        let file = self.csv_file();
+        export_csv(&rows, &file)?;
+        self.status = format!("Exported {} rows to {}", rows.len(), file.display());
+        Ok(())
     }
 
-    fn write_file(&self) -> Result<(), String> {
-        write_diagnostics(&self.items, &self.config, &self.model)
+    fn import_csv(&mut self) -> Result<(), String> {
+        let file = self.csv_file();
+        let count = import_csv(&mut self.items, &file)?;
+        let refused = clear_must_fix(&self.config, &self.model, &mut self.items);
+        self.status = match refused {
+            0 => format!("Imported {count} justifications"),
+            _ => format!(
+                "Imported {} justifications, refused {refused} for rules that must be fixed",
+                count - refused
+            ),
+        };
+        Ok(())
+    }
+
+    /// Writes an html report of the current diagnostics, including
+    /// justifications that haven't been written to the suppression file yet.
+    fn export_html(&self) -> Result<(), String> {
+        let report = Report {
+            model: self.model.clone(),
+            diagnostics: self.items.clone(),
+            suppressions: read_suppressions(&self.config, &self.model)?,
+            metadata_dir: metadata_dir(&self.config, &self.model),
+            catalogue: Catalogue::new(&self.config),
+            must_fix: Policy::must_fix(&self.config, &self.model),
+        };
+        let html = render_report(&report, ReportFormat::Html)?;
+        match fs::write(format!("{}_report.html", &self.model), html) {
+            Ok(_) => Ok(()),
+            Err(_) => Err("Could not write html report".to_owned()),
+        }
     }
 
     pub fn set_error(&mut self, error_message: String) {
@@ -184,18 +582,115 @@ impl App {
 
     pub fn set_model(&mut self, model: String) {
         self.model = model;
-        match read_diagnostics(&self.config, &self.model) {
-            Ok(data) => {
-                self.items = data
-                    .into_iter()
-                    .filter(|d| d.Severity != "Informational")
-                    .collect();
+        self.source = None;
+        self.suggestions = collect_suggestions(&self.config);
+        self.meta = read_meta(&self.config, &self.model).unwrap_or_default();
+        match Owners::read(&self.config) {
+            Ok(owners) => self.owners = owners,
+            Err(e) => return self.set_error(e),
+        }
+        match read_actionable_diagnostics(&self.config, &self.model) {
+            Ok(mut data) => {
+                if self.config.workflow {
+                    apply_drafts(&self.meta, &mut data);
+                }
+                clear_must_fix(&self.config, &self.model, &mut data);
+                self.loaded = data.iter().map(|d| d.Justification.clone()).collect();
+                // Unsaved justifications from the last run win over drafts
+                // from the metadata file.
+                if let Some(state) = &self.session {
+                    state.restore_drafts(&self.model, &mut data);
+                    clear_must_fix(&self.config, &self.model, &mut data);
+                }
+                self.items = data;
+                self.update_rows();
                 self.state.select(Some(0))
             }
             Err(e) => self.set_error(e),
         }
     }
 
+    /// Reopens the model of the last run with its view, owner filter,
+    /// selected row and unsaved justifications.
+    pub fn restore_session(&mut self) {
+        let Some(state) = self.session.clone() else {
+            return;
+        };
+        if self.config.needs_profile() {
+            match state.profile.as_deref().map(|p| self.config.use_profile(p)) {
+                Some(Ok(())) => {}
+                _ => return,
+            }
+        }
+        let Some(model) = state.model.filter(|m| self.config.models.contains(m)) else {
+            return;
+        };
+        self.set_model(model);
+        if let InputMode::Error = self.mode {
+            return;
+        }
+        match state.review && self.config.workflow {
+            true => self.set_mode(InputMode::Review),
+            false => self.set_mode(InputMode::Normal),
+        }
+        self.owner_filter = state
+            .owner_filter
+            .filter(|o| self.owners.all().contains(&o.as_str()));
+        self.update_rows();
+        let count = self.visible().len();
+        self.state
+            .select(state.selected.filter(|i| *i < count).or(Some(0)));
+    }
+
+    /// Saves the model, view, owner filter, selected row and the
+    /// justifications not written yet, so they survive the app or the session
+    /// being closed. Only done in the main and review views, so not for every
+    /// character typed.
+    pub fn save_session(&mut self) {
+        if !matches!(self.mode, InputMode::Normal | InputMode::Review) {
+            return;
+        }
+        let Some(mut state) = self.session.clone() else {
+            return;
+        };
+        state.profile = self.config.profile.clone();
+        state.model = Some(self.model.clone());
+        state.selected = self.state.selected();
+        state.owner_filter = self.owner_filter.clone();
+        state.review = matches!(self.mode, InputMode::Review);
+        state.set_drafts(&self.model, &self.items, &self.loaded);
+        self.store_session(state);
+    }
+
+    /// Writes the state file if the state changed.
+    fn store_session(&mut self, state: State) {
+        if self.session.as_ref() == Some(&state) {
+            return;
+        }
+        match save_state(&state) {
+            Ok(()) => self.session = Some(state),
+            Err(e) => self.status = e,
+        }
+    }
+
+    /// Switches to the selected profile and shows its models.
+    pub fn select_profile(&mut self) {
+        let Some(profile) = self
+            .state
+            .selected()
+            .and_then(|i| self.config.profiles.keys().nth(i))
+        else {
+            return;
+        };
+        match self.config.use_profile(&profile.clone()) {
+            Ok(()) => {
+                self.state.select(Some(0));
+                self.set_mode(InputMode::ModelSelect)
+            }
+            Err(e) => self.status = e,
+        }
+    }
+
     pub fn get_selected_model(&self) -> Option<&String> {
         if self.config.models.len() < 1 {
             return None;
@@ -205,6 +700,16 @@ impl App {
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let profile = match parse_args(&args) {
+        Ok((Command::Tui, profile)) => profile,
+        Ok((command, profile)) => run_command(&command, profile.as_deref()),
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            process::exit(2);
+        }
+    };
+
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -212,8 +717,16 @@ fn main() -> Result<(), Box<dyn Error>> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let app = match read_config() {
-        Ok(config) => App::new(vec![], config, String::new()),
+    let app = match read_config(profile.as_deref()) {
+        Ok(config) => {
+            let needs_profile = config.needs_profile();
+            let mut app = App::new(vec![], config, String::new());
+            if needs_profile {
+                app.set_mode(InputMode::ProfileSelect);
+            }
+            app.restore_session();
+            app
+        }
         Err(e) => {
             let mut app = App::new(vec![], Config::default(), String::new());
             app.set_error(e);
@@ -238,36 +751,108 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+fn run_command(command: &Command, profile: Option<&str>) -> ! {
+    let result = match command {
+        Command::Help => Ok(USAGE.to_owned()),
+        _ => read_config(profile).and_then(|config| cli::run(command, &config)),
+    };
+    match result {
+        Ok(output) => {
+            use io::Write;
+            // Piping into e.g. `head` closes stdout early, which isn't an error.
+            match writeln!(io::stdout().lock(), "{output}") {
+                Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+                    eprintln!("Could not write output: {e}");
+                    process::exit(1);
+                }
+                _ => process::exit(0),
+            }
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Leaves the terminal UI while the editor runs on the selected
+/// diagnostic's metadata file and restores it afterwards.
+fn open_editor<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> {
+    let Some(location) = app.get_selected().and_then(|d| app.location(d)) else {
+        app.status = "No metadata file found".to_owned();
+        return Ok(());
+    };
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+    let result = open_in_editor(&app.config, &location);
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+    terminal.clear()?;
+    // The file may have been changed in the editor.
+    app.source = None;
+    if let Err(e) = result {
+        app.status = e;
+    }
+    Ok(())
+}
+
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
     loop {
         terminal.draw(|f| ui(f, &mut app))?;
 
         if let Event::Key(key) = event::read()? {
             if key.kind == KeyEventKind::Press {
-                use KeyCode::*;
+                use Action::*;
+                app.status.clear();
+                let action = app.keys.action(app.mode.name(), key.code);
                 match app.mode {
-                    InputMode::Normal => match key.code {
-                        Char('q') => return Ok(()),
-                        Char('j') | Down => app.next(app.items.len()),
-                        Char('k') | Up => app.previous(app.items.len()),
-                        Char('l') | Right => app.next_color(),
-                        Char('h') | Left => app.previous_color(),
-                        Char('w') => match app.write_file() {
-                            Err(e) => app.set_error(e),
-                            _ => {}
-                        },
-                        Enter => app.set_mode(InputMode::Justification),
-                        Char('m') | Esc => app.set_mode(InputMode::ModelSelect),
+                    InputMode::Normal => match action {
+                        Some(Quit) => return Ok(()),
+                        Some(Down) => app.next(app.visible().len()),
+                        Some(Up) => app.previous(app.visible().len()),
+                        Some(NextColor) => app.next_color(),
+                        Some(PreviousColor) => app.previous_color(),
+                        Some(Write) => app.write_file(false),
+                        Some(WriteForce) => app.write_file(true),
+                        Some(ExportCsv) => {
+                            if let Err(e) = app.export_csv() {
+                                app.set_error(e)
+                            }
+                        }
+                        Some(ImportCsv) => {
+                            if let Err(e) = app.import_csv() {
+                                app.set_error(e)
+                            }
+                        }
+                        Some(Report) => {
+                            if let Err(e) = app.export_html() {
+                                app.set_error(e)
+                            }
+                        }
+                        Some(WorkItem) => app.set_mode(InputMode::WorkItem),
+                        Some(Expiry) => app.edit_expiry(),
+                        Some(Owner) => app.next_owner(),
+                        Some(Source) => app.show_source = !app.show_source,
+                        Some(Editor) => open_editor(terminal, &mut app)?,
+                        Some(RuleInfo) => app.show_rule_info = !app.show_rule_info,
+                        Some(Propose) => app.propose(),
+                        Some(Review) => app.set_view(InputMode::Review),
+                        Some(Justify) => app.edit_justification(),
+                        Some(SwitchModel) => app.set_mode(InputMode::ModelSelect),
                         _ => {}
                     },
 
-                    InputMode::Justification => match key.code {
-                        Esc | Enter => app.set_mode(InputMode::Normal),
-                        Char(c) => match app.get_selected_mut() {
+                    InputMode::Justification => match (action, key.code) {
+                        (Some(Back), _) => app.set_mode(InputMode::Normal),
+                        (Some(Snippets), _) if !app.snippets().is_empty() => {
+                            app.snippet_state.select(Some(0));
+                            app.set_mode(InputMode::Snippet)
+                        }
+                        (_, KeyCode::Char(c)) => match app.get_selected_mut() {
                             Some(s) => s.Justification.push(c),
                             None => {}
                         },
-                        Backspace => match app.get_selected_mut() {
+                        (_, KeyCode::Backspace) => match app.get_selected_mut() {
                             Some(s) => {
                                 s.Justification.pop();
                             }
@@ -275,17 +860,61 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                         },
                         _ => {}
                     },
-                    InputMode::Error => match key.code {
-                        Char('q') | Esc => return Ok(()),
+                    InputMode::Snippet => match action {
+                        Some(Cancel) => app.set_mode(InputMode::Justification),
+                        Some(Down) => app.next_snippet(),
+                        Some(Up) => app.previous_snippet(),
+                        Some(Insert) => {
+                            app.insert_snippet();
+                            app.set_mode(InputMode::Justification)
+                        }
+                        _ => {}
+                    },
+                    InputMode::WorkItem => match (action, key.code) {
+                        (Some(Back), _) => app.set_mode(InputMode::Normal),
+                        (_, KeyCode::Char(c)) => app.work_item.push(c),
+                        (_, KeyCode::Backspace) => {
+                            app.work_item.pop();
+                        }
+                        _ => {}
+                    },
+                    InputMode::Expiry => match (action, key.code) {
+                        (Some(Cancel), _) => app.set_mode(InputMode::Normal),
+                        (Some(Save), _) => {
+                            app.save_expiry();
+                            app.set_mode(InputMode::Normal)
+                        }
+                        (_, KeyCode::Char(c)) => app.expiry.push(c),
+                        (_, KeyCode::Backspace) => {
+                            app.expiry.pop();
+                        }
+                        _ => {}
+                    },
+                    InputMode::Review => match action {
+                        Some(Back) => app.set_view(InputMode::Normal),
+                        Some(Down) => app.next(app.visible().len()),
+                        Some(Up) => app.previous(app.visible().len()),
+                        Some(Approve) => app.review(ReviewState::Approved, false),
+                        Some(ApproveAll) => app.review(ReviewState::Approved, true),
+                        Some(Reject) => app.review(ReviewState::Rejected, false),
                         _ => {}
                     },
-                    InputMode::ModelSelect => match key.code {
-                        Char('q') | Esc => return Ok(()),
-                        Char('j') | Down => app.next(app.config.models.len()),
-                        Char('k') | Up => app.previous(app.config.models.len()),
-                        Char('l') | Right => app.next_color(),
-                        Char('h') | Left => app.previous_color(),
-                        Enter => match app.get_selected_model() {
+                    InputMode::Error => {
+                        if action == Some(Quit) {
+                            return Ok(());
+                        }
+                    }
+                    InputMode::ModelSelect => match action {
+                        Some(Quit) => return Ok(()),
+                        Some(Down) => app.next(app.config.models.len()),
+                        Some(Up) => app.previous(app.config.models.len()),
+                        Some(NextColor) => app.next_color(),
+                        Some(PreviousColor) => app.previous_color(),
+                        Some(Profiles) => {
+                            app.state.select(Some(0));
+                            app.set_mode(InputMode::ProfileSelect)
+                        }
+                        Some(Select) => match app.get_selected_model() {
                             Some(m) => {
                                 app.set_model(m.clone());
                                 app.set_mode(InputMode::Normal)
@@ -294,14 +923,25 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                         },
                         _ => {}
                     },
+
+                    InputMode::ProfileSelect => match action {
+                        Some(Quit) => return Ok(()),
+                        Some(Down) => app.next(app.config.profiles.len()),
+                        Some(Up) => app.previous(app.config.profiles.len()),
+                        Some(NextColor) => app.next_color(),
+                        Some(PreviousColor) => app.previous_color(),
+                        Some(Select) => app.select_profile(),
+                        _ => {}
+                    },
                 }
+                app.save_session();
             }
         }
     }
 }
 
 fn ui(f: &mut Frame, app: &mut App) {
-    app.set_colors();
+    app.cache_source();
     match app.mode {
         InputMode::Error => {
             let layout = Layout::vertical([
@@ -314,7 +954,7 @@ fn ui(f: &mut Frame, app: &mut App) {
             render_error(f, app, layout[0]);
             render_footer(f, app, layout[2]);
         }
-        InputMode::ModelSelect => {
+        InputMode::ModelSelect | InputMode::ProfileSelect => {
             let rects =
                 Layout::vertical([Constraint::Min(5), Constraint::Length(3)]).split(f.size());
 
@@ -331,11 +971,36 @@ fn ui(f: &mut Frame, app: &mut App) {
             ])
             .split(f.size());
 
-            render_bp(f, app, rects[0]);
-            render_scrollbar(f, app, rects[0]);
+            let table_area = match (app.show_source, app.show_rule_info) {
+                (false, false) => rects[0],
+                (show_source, show_rule_info) => {
+                    let halves = Layout::horizontal([
+                        Constraint::Percentage(50),
+                        Constraint::Percentage(50),
+                    ])
+                    .split(rects[0]);
+                    match (show_source, show_rule_info) {
+                        (true, true) => {
+                            let panes =
+                                Layout::vertical([Constraint::Length(10), Constraint::Min(5)])
+                                    .split(halves[1]);
+                            render_rule_info(f, app, panes[0]);
+                            render_source(f, app, panes[1]);
+                        }
+                        (true, false) => render_source(f, app, halves[1]),
+                        _ => render_rule_info(f, app, halves[1]),
+                    }
+                    halves[0]
+                }
+            };
+            render_bp(f, app, table_area);
+            render_scrollbar(f, app, table_area);
             render_justification(f, app, rects[1]);
             render_cur_details(f, app, rects[2]);
             render_footer(f, app, rects[3]);
+            if let InputMode::Snippet = app.mode {
+                render_snippets(f, app, rects[0]);
+            }
         }
     }
 }
@@ -348,44 +1013,71 @@ fn render_bp(f: &mut Frame, app: &mut App, area: Rect) {
         .add_modifier(Modifier::REVERSED)
         .fg(app.colors.selected_style_fg);
 
-    let header = ["Moniker", "Severity", "Path"]
+    // The owner column is only there when an owners file is configured.
+    let show_owners = app.config.owners.is_some();
+    let owner_header = match &app.owner_filter {
+        Some(owner) => format!("Owner: {owner}"),
+        None => "Owner".to_owned(),
+    };
+    let mut headers = vec!["Moniker", "Severity", &owner_header, "Path"];
+    let mut widths = vec![
+        // + 1 is for padding.
+        Constraint::Length(20),
+        Constraint::Length(20),
+        Constraint::Length(20),
+        Constraint::Min(20),
+    ];
+    if !show_owners {
+        headers.remove(2);
+        widths.remove(2);
+    }
+    let header = headers
         .into_iter()
         .map(Cell::from)
         .collect::<Row>()
         .style(header_style)
         .height(1);
-    let rows = app.items.iter().enumerate().map(|(i, data)| {
-        let color = match i % 2 {
-            0 => app.colors.normal_row_color,
-            _ => app.colors.alt_row_color,
-        };
-        let item = data.ref_array();
-        item.into_iter()
-            .map(|content| Cell::from(Text::from(format!("{content}"))))
-            .collect::<Row>()
-            .style(Style::new().fg(app.colors.row_fg).bg(color))
-            .height(1)
-    });
+    let visible = app.visible();
+    let rows = visible
+        .iter()
+        .map(|i| &app.items[*i])
+        .enumerate()
+        .map(|(i, data)| {
+            let color = match i % 2 {
+                0 => app.colors.normal_row_color,
+                _ => app.colors.alt_row_color,
+            };
+            let expired = app.meta.iter().any(|m| m.is_for(data) && m.is_expired());
+            let fg = match app.linter.check(&data.Justification).is_empty() {
+                _ if expired => app.colors.expired_fg,
+                true => app.colors.row_fg,
+                false => app.colors.error_fg,
+            };
+            let [moniker, severity, path] = data.ref_array();
+            let owner = app.owners.owners_of(path).join(" ");
+            let mut cells = vec![moniker, severity, &owner, path];
+            if !show_owners {
+                cells.remove(2);
+            }
+            cells
+                .into_iter()
+                .map(|content| Cell::from(Text::from(content.to_string())))
+                .collect::<Row>()
+                .style(Style::new().fg(fg).bg(color))
+                .height(1)
+        });
     let bar = " █ ";
-    let t = Table::new(
-        rows,
-        [
-            // + 1 is for padding.
-            Constraint::Length(20),
-            Constraint::Length(20),
-            Constraint::Min(20),
-        ],
-    )
-    .header(header)
-    .highlight_style(selected_style)
-    .highlight_symbol(Text::from(vec![
-        "".into(),
-        bar.into(),
-        bar.into(),
-        "".into(),
-    ]))
-    .bg(app.colors.buffer_bg)
-    .highlight_spacing(HighlightSpacing::Always);
+    let t = Table::new(rows, widths)
+        .header(header)
+        .highlight_style(selected_style)
+        .highlight_symbol(Text::from(vec![
+            "".into(),
+            bar.into(),
+            bar.into(),
+            "".into(),
+        ]))
+        .bg(app.colors.buffer_bg)
+        .highlight_spacing(HighlightSpacing::Always);
     f.render_stateful_widget(t, area, &mut app.state);
 }
 
@@ -404,11 +1096,9 @@ fn render_scrollbar(f: &mut Frame, app: &mut App, area: Rect) {
 }
 
 fn render_footer(f: &mut Frame, app: &App, area: Rect) {
-    let info_footer = Paragraph::new(Line::from(match app.mode {
-        InputMode::Justification => INFO_TEXT_JUSTIFICATION,
-        InputMode::Error => INFO_TEXT_ERROR,
-        InputMode::ModelSelect => INFO_TEXT_MODELSELECT,
-        _ => INFO_TEXT_NORMAL,
+    let info_footer = Paragraph::new(Line::from(match app.status.is_empty() {
+        true => app.keys.help(app.mode.name()),
+        false => app.status.clone(),
     }))
     .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
     .centered()
@@ -423,7 +1113,28 @@ fn render_footer(f: &mut Frame, app: &App, area: Rect) {
 
 fn render_cur_details(f: &mut Frame, app: &App, area: Rect) {
     let info = match app.get_selected() {
-        Some(s) => s.info().clone(),
+        Some(s) => {
+            let mut info = s.info();
+            if let Some(l) = app.selected_location() {
+                let dir = metadata_dir(&app.config, &app.model);
+                info.push_str(&format!("  --  File: {}", l.short(&dir)));
+            }
+            if let Some(m) = app.selected_meta() {
+                if !m.author.is_empty() {
+                    info.push_str(&format!("  --  Justified by {} on {}", m.author, m.date));
+                }
+                if let Some(state) = m.state {
+                    info.push_str(&format!("  --  State: {}", state.name()));
+                }
+                if let Some(expires) = &m.expires {
+                    match m.is_expired() {
+                        true => info.push_str(&format!("  --  EXPIRED on {expires}")),
+                        false => info.push_str(&format!("  --  Expires {expires}")),
+                    }
+                }
+            }
+            info
+        }
         None => "None selected".to_owned(),
     };
 
@@ -440,16 +1151,24 @@ fn render_cur_details(f: &mut Frame, app: &App, area: Rect) {
 }
 
 fn render_justification(f: &mut Frame, app: &App, area: Rect) {
-    let text = format!(
-        "Justification: {}",
-        match app.get_selected() {
-            Some(s) => s.Justification.clone(),
-            None => "".to_owned(),
-        }
-    );
+    let (justification, problems) = match app.get_selected() {
+        Some(s) => (s.Justification.clone(), app.linter.check(&s.Justification)),
+        None => ("".to_owned(), vec![]),
+    };
+    let mut text = match app.mode {
+        InputMode::WorkItem => format!("Work item: {}", app.work_item),
+        InputMode::Expiry => format!("Expires: {}", app.expiry),
+        _ => format!("Justification: {justification}"),
+    };
+    if !problems.is_empty() {
+        text.push_str(&format!("  [{}]", problems.join(", ")));
+    }
     let info_footer = Paragraph::new(Line::from(text))
         .style(match app.mode {
-            InputMode::Justification => Style::default().fg(Color::Yellow),
+            InputMode::Justification
+            | InputMode::Snippet
+            | InputMode::WorkItem
+            | InputMode::Expiry => Style::default().fg(app.colors.edit_fg),
             _ => Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg),
         })
         // .centered()
@@ -483,13 +1202,23 @@ fn render_models(f: &mut Frame, app: &mut App, area: Rect) {
         .add_modifier(Modifier::REVERSED)
         .fg(app.colors.selected_style_fg);
 
-    let header = ["Name"]
+    let (title, names): (String, Vec<&String>) = match app.mode {
+        InputMode::ProfileSelect => ("Profile".to_owned(), app.config.profiles.keys().collect()),
+        _ => match &app.config.profile {
+            Some(profile) => (
+                format!("Name ({profile})"),
+                app.config.models.iter().collect(),
+            ),
+            None => ("Name".to_owned(), app.config.models.iter().collect()),
+        },
+    };
+    let header = [title]
         .into_iter()
         .map(Cell::from)
         .collect::<Row>()
         .style(header_style)
         .height(1);
-    let rows = app.config.models.iter().enumerate().map(|(i, model)| {
+    let rows = names.into_iter().enumerate().map(|(i, model)| {
         let color = match i % 2 {
             0 => app.colors.normal_row_color,
             _ => app.colors.alt_row_color,
@@ -515,3 +1244,124 @@ fn render_models(f: &mut Frame, app: &mut App, area: Rect) {
         .highlight_spacing(HighlightSpacing::Always);
     f.render_stateful_widget(t, area, &mut app.state);
 }
+
+fn render_snippets(f: &mut Frame, app: &mut App, area: Rect) {
+    let selected_style = Style::default()
+        .add_modifier(Modifier::REVERSED)
+        .fg(app.colors.selected_style_fg);
+    let rows: Vec<Row> = app
+        .snippets()
+        .into_iter()
+        .map(|(text, count)| {
+            let used = match count {
+                Some(count) => format!("used {count}×"),
+                None => "snippet".to_owned(),
+            };
+            Row::new([text, used]).style(Style::new().fg(app.colors.row_fg))
+        })
+        .collect();
+    let height = cmp::min(rows.len() as u16 + 2, area.height);
+    let popup = Rect {
+        x: area.x + area.width / 8,
+        y: area.y + (area.height - height) / 2,
+        width: area.width - area.width / 4,
+        height,
+    };
+    let t = Table::new(rows, [Constraint::Min(20), Constraint::Length(10)])
+        .highlight_style(selected_style)
+        .bg(app.colors.buffer_bg)
+        .block(
+            Block::default()
+                .title("Snippets and previous justifications")
+                .borders(Borders::ALL)
+                .border_style(Style::new().fg(app.colors.footer_border_color))
+                .border_type(BorderType::Double),
+        );
+    f.render_widget(Clear, popup);
+    f.render_stateful_widget(t, popup, &mut app.snippet_state);
+}
+
+/// X++ source of the selected diagnostic's element, scrolled to and
+/// highlighting the method the diagnostic is about.
+fn render_source(f: &mut Frame, app: &App, area: Rect) {
+    let method = app.get_selected().and_then(|d| method_name(&d.Path));
+    let mut lines: Vec<Line> = vec![];
+    let mut offset = 0;
+    let title = match app.selected_location() {
+        Some(l) => l.short(&metadata_dir(&app.config, &app.model)),
+        None => "Source".to_owned(),
+    };
+    match app.source.as_ref().and_then(|c| c.blocks.as_ref()) {
+        Some(Ok(blocks)) => {
+            offset = method
+                .and_then(|m| method_offset(blocks, m))
+                .unwrap_or_default();
+            for block in blocks {
+                let style = match Some(block.name.as_str()) == method {
+                    true => Style::new().fg(app.colors.edit_fg),
+                    false => Style::new().fg(app.colors.row_fg),
+                };
+                lines.push(Line::styled(
+                    format!("// {}", block.name),
+                    Style::new().fg(app.colors.selected_style_fg),
+                ));
+                for line in block.lines() {
+                    lines.push(Line::styled(line, style));
+                }
+                lines.push(Line::from(""));
+            }
+        }
+        Some(Err(e)) => lines.push(Line::from(e.as_str())),
+        None => lines.push(Line::from("No metadata file found")),
+    }
+    let source = Paragraph::new(lines)
+        .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+        .scroll((offset as u16, 0))
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::new().fg(app.colors.footer_border_color))
+                .border_type(BorderType::Double),
+        );
+    f.render_widget(source, area);
+}
+
+fn render_rule_info(f: &mut Frame, app: &App, area: Rect) {
+    let moniker = app
+        .get_selected()
+        .map(|d| d.Moniker.clone())
+        .unwrap_or_default();
+    let lines: Vec<Line> = match app.catalogue.get(&moniker) {
+        Some(info) => vec![
+            Line::styled(
+                info.title.clone(),
+                Style::new().fg(app.colors.selected_style_fg),
+            ),
+            Line::from(info.explanation.clone()),
+            Line::from(format!("Fix: {}", info.fix)),
+            Line::from(
+                match (
+                    Policy::of(&app.config, &app.model, &moniker),
+                    info.suppressible,
+                ) {
+                    (Policy::MustFix, _) => "Must be fixed, justifications are refused",
+                    (_, true) => "May be suppressed with a justification",
+                    (_, false) => "Should be fixed, not suppressed",
+                },
+            ),
+        ],
+        None => vec![Line::from("No description for this rule")],
+    };
+    let rule_info = Paragraph::new(lines)
+        .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .title(moniker)
+                .borders(Borders::ALL)
+                .border_style(Style::new().fg(app.colors.footer_border_color))
+                .border_type(BorderType::Double),
+        );
+    f.render_widget(rule_info, area);
+}
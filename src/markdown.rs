@@ -0,0 +1,76 @@
+use std::fmt::Write;
+
+use crate::{read::Diagnostic, report::Report};
+
+/// Keeps table cells on one line and stops `|` from splitting them.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+/// Renders a summary table per moniker and a collapsible list of the new
+/// diagnostics that have neither a suppression nor a justification.
+pub fn to_markdown(report: &Report) -> Result<String, String> {
+    let mut md = String::new();
+    let _ = writeln!(md, "### Best practices: {}\n", cell(&report.model));
+    md.push_str("| Moniker | Rule | Severity | Total | Justified | Unjustified |\n");
+    md.push_str("| --- | --- | --- | ---: | ---: | ---: |\n");
+    for moniker in report.monikers() {
+        let diagnostics: Vec<&Diagnostic> = report
+            .diagnostics
+            .iter()
+            .filter(|d| d.Moniker == moniker)
+            .collect();
+        let mut severities: Vec<&str> = vec![];
+        for d in &diagnostics {
+            if !severities.contains(&d.Severity.as_str()) {
+                severities.push(&d.Severity);
+            }
+        }
+        let justified = diagnostics
+            .iter()
+            .filter(|d| report.justification(d).is_some())
+            .count();
+        let _ = writeln!(
+            md,
+            "| {} | {} | {} | {} | {} | {} |",
+            cell(moniker),
+            cell(
+                report
+                    .catalogue
+                    .get(moniker)
+                    .map_or("", |info| info.title.as_str())
+            ),
+            cell(&severities.join(", ")),
+            diagnostics.len(),
+            justified,
+            diagnostics.len() - justified
+        );
+    }
+
+    let new: Vec<&Diagnostic> = report
+        .diagnostics
+        .iter()
+        .filter(|d| report.suppression(d).is_none() && report.justification(d).is_none())
+        .collect();
+    if new.is_empty() {
+        md.push_str("\nNo new unjustified diagnostics.\n");
+        return Ok(md);
+    }
+    let _ = writeln!(
+        md,
+        "\n<details>\n<summary>{} new unjustified diagnostics</summary>\n",
+        new.len()
+    );
+    for d in new {
+        let _ = writeln!(
+            md,
+            "- **{}** `{}` {}: {}",
+            d.Severity,
+            d.Path,
+            d.Moniker,
+            cell(&d.Message)
+        );
+    }
+    md.push_str("\n</details>\n");
+    Ok(md)
+}
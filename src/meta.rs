@@ -0,0 +1,404 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Config, read::Diagnostic, write::suppressions_file};
+
+/// Review state of a justification when the workflow is enabled.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewState {
+    #[default]
+    Draft,
+    Proposed,
+    Approved,
+    Rejected,
+}
+
+impl ReviewState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Proposed => "proposed",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+/// Metadata about a suppression that the D365 xml has no place for. Kept in
+/// a sidecar file next to the suppression file, keyed by path and moniker.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct SuppressionMeta {
+    pub path: String,
+    pub moniker: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub author: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub work_item: Option<String>,
+    /// Date (`YYYY-MM-DD`) after which the suppression has to be revisited.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<ReviewState>,
+    /// The justification under review; only written to the suppression file
+    /// once approved.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub justification: Option<String>,
+}
+
+impl SuppressionMeta {
+    pub fn is_for(&self, d: &Diagnostic) -> bool {
+        self.path == d.Path && self.moniker == d.Moniker
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_on(&now_utc()[..10])
+    }
+
+    /// Suppressions are valid up to and including their expiry date.
+    fn is_expired_on(&self, today: &str) -> bool {
+        match &self.expires {
+            Some(expires) => expires.as_str() < today,
+            None => false,
+        }
+    }
+
+    /// Whether the justification may be written to the suppression file.
+    pub fn is_approved(&self, justification: &str) -> bool {
+        self.state == Some(ReviewState::Approved)
+            && self.justification.as_deref() == Some(justification)
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Sidecar {
+    #[serde(default)]
+    suppression: Vec<SuppressionMeta>,
+}
+
+/// `<model>_BPSuppressions.meta.toml` next to the suppression file.
+pub fn meta_file(supp_file_path: &Path) -> PathBuf {
+    supp_file_path.with_extension("meta.toml")
+}
+
+fn load_meta(file: &Path) -> Result<Vec<SuppressionMeta>, String> {
+    if !file.exists() {
+        return Ok(vec![]);
+    }
+    let toml = match fs::read_to_string(file) {
+        Ok(toml) => toml,
+        Err(_) => return Err(format!("Could not read {}", file.display())),
+    };
+    match toml::from_str::<Sidecar>(&toml) {
+        Ok(sidecar) => Ok(sidecar.suppression),
+        Err(_) => Err(format!("Could not parse {}", file.display())),
+    }
+}
+
+fn save_meta(file: &Path, meta: Vec<SuppressionMeta>) -> Result<(), String> {
+    let toml = match toml::to_string(&Sidecar { suppression: meta }) {
+        Ok(toml) => toml,
+        Err(_) => return Err("Could not serialize suppression metadata".to_owned()),
+    };
+    match fs::write(file, toml) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("Could not write {}", file.display())),
+    }
+}
+
+pub fn read_meta(config: &Config, model: &String) -> Result<Vec<SuppressionMeta>, String> {
+    load_meta(&meta_file(&suppressions_file(config, model)?))
+}
+
+fn entry<'a>(meta: &'a mut Vec<SuppressionMeta>, d: &Diagnostic) -> &'a mut SuppressionMeta {
+    match meta.iter().position(|m| m.is_for(d)) {
+        Some(i) => &mut meta[i],
+        None => {
+            meta.push(SuppressionMeta {
+                path: d.Path.clone(),
+                moniker: d.Moniker.clone(),
+                ..Default::default()
+            });
+            meta.last_mut().unwrap()
+        }
+    }
+}
+
+/// Sets or clears the expiry date of the suppression for a diagnostic and
+/// returns the updated metadata.
+pub fn set_expiry(
+    config: &Config,
+    model: &String,
+    d: &Diagnostic,
+    expires: Option<&str>,
+) -> Result<Vec<SuppressionMeta>, String> {
+    if let Some(date) = expires {
+        if !is_date(date) {
+            return Err(format!("Invalid date {date}, expected YYYY-MM-DD"));
+        }
+    }
+    let file = meta_file(&suppressions_file(config, model)?);
+    let mut meta = load_meta(&file)?;
+    entry(&mut meta, d).expires = expires.map(|e| e.to_owned());
+    save_meta(&file, meta.clone())?;
+    Ok(meta)
+}
+
+/// Stores new or changed justifications as drafts for review, recording
+/// the current user as their author. Justifications already in the
+/// suppression file are left alone.
+pub fn save_drafts(
+    config: &Config,
+    model: &String,
+    data: &[Diagnostic],
+    suppressions: &[Diagnostic],
+    work_item: Option<&str>,
+) -> Result<Vec<SuppressionMeta>, String> {
+    let file = meta_file(&suppressions_file(config, model)?);
+    let mut meta = load_meta(&file)?;
+    let author = current_user();
+    let date = now_utc();
+    for d in data {
+        if d.Justification.is_empty() {
+            continue;
+        }
+        let known = meta
+            .iter()
+            .find(|m| m.is_for(d))
+            .and_then(|m| m.justification.as_deref());
+        let suppressed = suppressions
+            .iter()
+            .any(|s| s.same_as(d) && s.Justification == d.Justification);
+        if known == Some(d.Justification.as_str()) || (known.is_none() && suppressed) {
+            continue;
+        }
+        let entry = entry(&mut meta, d);
+        entry.justification = Some(d.Justification.clone());
+        entry.state = Some(ReviewState::Draft);
+        entry.author = author.clone();
+        entry.date = date.clone();
+        entry.work_item = work_item.map(|w| w.to_owned());
+    }
+    save_meta(&file, meta.clone())?;
+    Ok(meta)
+}
+
+/// Fills in empty justifications from the drafts under review, for the
+/// review workflow.
+pub fn apply_drafts(meta: &[SuppressionMeta], data: &mut [Diagnostic]) {
+    for d in data.iter_mut().filter(|d| d.Justification.is_empty()) {
+        if let Some(j) = meta
+            .iter()
+            .find(|m| m.is_for(d))
+            .and_then(|m| m.justification.as_ref())
+        {
+            d.Justification = j.clone();
+        }
+    }
+}
+
+/// Moves the justifications of the given diagnostics to a new review state.
+pub fn set_state(
+    config: &Config,
+    model: &String,
+    data: &[&Diagnostic],
+    state: ReviewState,
+) -> Result<Vec<SuppressionMeta>, String> {
+    let file = meta_file(&suppressions_file(config, model)?);
+    let mut meta = load_meta(&file)?;
+    for d in data {
+        if let Some(m) = meta.iter_mut().find(|m| m.is_for(d)) {
+            if m.justification.is_some() {
+                m.state = Some(state);
+            }
+        }
+    }
+    save_meta(&file, meta.clone())?;
+    Ok(meta)
+}
+
+/// Whether the text is a valid `YYYY-MM-DD` date.
+fn is_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    let digits = parts.len() == 3
+        && [4, 2, 2]
+            .iter()
+            .zip(&parts)
+            .all(|(len, p)| p.len() == *len && p.chars().all(|c| c.is_ascii_digit()));
+    if !digits {
+        return false;
+    }
+    let (Ok(year), Ok(month), Ok(day)) = (
+        parts[0].parse::<u32>(),
+        parts[1].parse::<u32>(),
+        parts[2].parse::<u32>(),
+    ) else {
+        return false;
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+/// Records the current user and time for the given (new or changed)
+/// suppressions. Justifications that went through review keep the author
+/// recorded when the draft was saved, rather than whoever writes them.
+pub fn record_changes(
+    supp_file_path: &Path,
+    changed: &[&Diagnostic],
+    work_item: Option<&str>,
+) -> Result<(), String> {
+    if changed.is_empty() {
+        return Ok(());
+    }
+    let file = meta_file(supp_file_path);
+    let mut meta = load_meta(&file)?;
+    let author = current_user();
+    let date = now_utc();
+    for d in changed {
+        let entry = entry(&mut meta, d);
+        if entry.justification.as_deref() == Some(d.Justification.as_str()) {
+            continue;
+        }
+        entry.author = author.clone();
+        entry.date = date.clone();
+        entry.work_item = work_item.map(|w| w.to_owned());
+    }
+    save_meta(&file, meta)
+}
+
+/// The git user name, falling back to the OS user.
+fn current_user() -> String {
+    if let Ok(output) = process::Command::new("git")
+        .args(["config", "user.name"])
+        .output()
+    {
+        let name = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+        if output.status.success() && !name.is_empty() {
+            return name;
+        }
+    }
+    env::var("USERNAME")
+        .or_else(|_| env::var("USER"))
+        .unwrap_or_else(|_| "unknown".to_owned())
+}
+
+/// Current time as `YYYY-MM-DDTHH:MM:SSZ`.
+pub fn now_utc() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    let time = secs % 86400;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// Converts days since 1970-01-01 to a (year, month, day) date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expiring(expires: Option<&str>) -> SuppressionMeta {
+        SuppressionMeta {
+            expires: expires.map(|e| e.to_owned()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn accepts_valid_dates() {
+        assert!(is_date("2024-01-31"));
+        assert!(is_date("2024-04-30"));
+        assert!(is_date("2024-12-01"));
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        for date in [
+            "2024-13-45",
+            "2024-00-00",
+            "2024-01-00",
+            "2024-04-31",
+            "2024-1-01",
+            "24-01-01",
+            "2024/01/01",
+            "2024-01-01-01",
+            "abcd-ef-gh",
+            "",
+        ] {
+            assert!(!is_date(date), "{date}");
+        }
+    }
+
+    #[test]
+    fn checks_leap_years() {
+        assert!(is_date("2024-02-29"));
+        assert!(is_date("2000-02-29"));
+        assert!(!is_date("2023-02-29"));
+        assert!(!is_date("1900-02-29"));
+        assert!(!is_date("2024-02-30"));
+    }
+
+    #[test]
+    fn converts_days_to_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(civil_from_days(11017), (2000, 3, 1));
+        assert_eq!(civil_from_days(19782), (2024, 2, 29));
+        assert_eq!(civil_from_days(19783), (2024, 3, 1));
+        assert_eq!(civil_from_days(19722), (2023, 12, 31));
+        assert_eq!(civil_from_days(19723), (2024, 1, 1));
+    }
+
+    #[test]
+    fn formats_the_current_time() {
+        let now = now_utc();
+        assert_eq!(now.len(), 20);
+        assert!(is_date(&now[..10]));
+        assert_eq!(&now[10..11], "T");
+        assert!(now.ends_with('Z'));
+    }
+
+    #[test]
+    fn expires_after_the_expiry_day() {
+        let meta = expiring(Some("2024-03-01"));
+        assert!(!meta.is_expired_on("2024-02-29"));
+        assert!(!meta.is_expired_on("2024-03-01"));
+        assert!(meta.is_expired_on("2024-03-02"));
+        assert!(meta.is_expired_on("2025-01-01"));
+        assert!(!expiring(None).is_expired_on("2099-12-31"));
+    }
+}
@@ -0,0 +1,125 @@
+use std::fs;
+
+use glob::Pattern;
+
+use crate::config::Config;
+
+/// Ownership rules from a CODEOWNERS-style file: each line is a glob on the
+/// logical path without `dynamics:/` (e.g. `/Class/Cust*`) followed by one or
+/// more owners. As in CODEOWNERS the last matching line wins.
+#[derive(Default)]
+pub struct Owners {
+    rules: Vec<(Pattern, Vec<String>)>,
+}
+
+impl Owners {
+    /// Reads the file from `owners` in the config; no rules if it isn't set.
+    pub fn read(config: &Config) -> Result<Self, String> {
+        let Some(file) = &config.owners else {
+            return Ok(Self::default());
+        };
+        let text = match fs::read_to_string(file) {
+            Ok(text) => text,
+            Err(_) => return Err(format!("Could not read owners file {file}")),
+        };
+        let mut rules = vec![];
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let pattern = parts.next().unwrap_or_default();
+            let owners: Vec<String> = parts.map(|o| o.to_owned()).collect();
+            match Pattern::new(pattern) {
+                Ok(p) => rules.push((p, owners)),
+                Err(_) => return Err(format!("Invalid pattern in owners file: {pattern}")),
+            }
+        }
+        Ok(Self { rules })
+    }
+
+    /// A pattern matches the path itself or any of its parents, so
+    /// `/Class/Cust*` covers `dynamics://Class/CustFoo/Method/bar`.
+    fn matches(pattern: &Pattern, path: &str) -> bool {
+        let path = path.strip_prefix("dynamics:/").unwrap_or(path);
+        path.match_indices('/')
+            .map(|(i, _)| &path[..i])
+            .chain([path])
+            .any(|p| pattern.matches(p))
+    }
+
+    pub fn owners_of(&self, path: &str) -> &[String] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(p, _)| Self::matches(p, path))
+            .map_or(&[], |(_, owners)| owners.as_slice())
+    }
+
+    pub fn is_owned_by(&self, path: &str, owner: &str) -> bool {
+        self.owners_of(path).iter().any(|o| o == owner)
+    }
+
+    /// All owners in order of appearance.
+    pub fn all(&self) -> Vec<&str> {
+        let mut all: Vec<&str> = vec![];
+        for (_, owners) in &self.rules {
+            for owner in owners {
+                if !all.contains(&owner.as_str()) {
+                    all.push(owner);
+                }
+            }
+        }
+        all
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owners(rules: &[(&str, &[&str])]) -> Owners {
+        Owners {
+            rules: rules
+                .iter()
+                .map(|(p, o)| {
+                    (
+                        Pattern::new(p).unwrap(),
+                        o.iter().map(|o| o.to_string()).collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn matches_the_path_and_its_parents() {
+        let pattern = Pattern::new("/Class/Cust*").unwrap();
+        assert!(Owners::matches(&pattern, "dynamics://Class/CustFoo"));
+        assert!(Owners::matches(
+            &pattern,
+            "dynamics://Class/CustFoo/Method/bar"
+        ));
+        assert!(Owners::matches(&pattern, "/Class/CustFoo"));
+        assert!(!Owners::matches(&pattern, "dynamics://Class/VendFoo"));
+        assert!(!Owners::matches(&pattern, "dynamics://Table/CustFoo"));
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let owners = owners(&[
+            ("/Class/*", &["@core"]),
+            ("/Class/Cust*", &["@sales", "@core"]),
+        ]);
+        assert_eq!(
+            owners.owners_of("dynamics://Class/CustFoo/Method/bar"),
+            ["@sales", "@core"]
+        );
+        assert_eq!(owners.owners_of("dynamics://Class/VendFoo"), ["@core"]);
+        assert!(owners.owners_of("dynamics://Table/CustFoo").is_empty());
+        assert!(owners.is_owned_by("dynamics://Class/CustFoo", "@sales"));
+        assert!(!owners.is_owned_by("dynamics://Class/VendFoo", "@sales"));
+        assert_eq!(owners.all(), ["@core", "@sales"]);
+    }
+}
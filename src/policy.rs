@@ -0,0 +1,117 @@
+use serde::Deserialize;
+
+use crate::{config::Config, read::Diagnostic};
+
+/// How a rule is handled, set per moniker in the `[policy]` section of the
+/// config. Rules without a policy may be suppressed.
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Policy {
+    /// Diagnostics have to be fixed, justifications are refused.
+    MustFix,
+    #[default]
+    SuppressAllowed,
+    /// Diagnostics are hidden everywhere.
+    AutoIgnore,
+}
+
+impl Policy {
+    /// The model's own policy for the moniker, otherwise the top level one.
+    pub fn of(config: &Config, model: &str, moniker: &str) -> Self {
+        config
+            .overrides
+            .get(model)
+            .and_then(|m| m.policy.get(moniker))
+            .or_else(|| config.policy.get(moniker))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Monikers of the model's must-fix rules.
+    pub fn must_fix(config: &Config, model: &str) -> Vec<String> {
+        let own = config.overrides.get(model).map(|m| m.policy.keys());
+        let mut monikers: Vec<String> = config
+            .policy
+            .keys()
+            .chain(own.into_iter().flatten())
+            .filter(|m| Self::of(config, model, m) == Self::MustFix)
+            .cloned()
+            .collect();
+        monikers.sort();
+        monikers.dedup();
+        monikers
+    }
+}
+
+/// Clears justifications that must-fix diagnostics got from somewhere else
+/// than the app, e.g. an import or a restored draft. Returns how many there
+/// were.
+pub fn clear_must_fix(config: &Config, model: &str, data: &mut [Diagnostic]) -> usize {
+    let mut cleared = 0;
+    for d in data {
+        if !d.Justification.is_empty() && Policy::of(config, model, &d.Moniker) == Policy::MustFix {
+            d.Justification.clear();
+            cleared += 1;
+        }
+    }
+    cleared
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ModelConfig;
+
+    fn config() -> Config {
+        let mut config = Config::default();
+        config
+            .policy
+            .insert("BPUnusedMethod".to_owned(), Policy::MustFix);
+        config
+            .policy
+            .insert("BPMissingLabel".to_owned(), Policy::MustFix);
+        let mut own = ModelConfig::default();
+        own.policy
+            .insert("BPMissingLabel".to_owned(), Policy::SuppressAllowed);
+        own.policy.insert("BPObsolete".to_owned(), Policy::MustFix);
+        config.overrides.insert("MyModel".to_owned(), own);
+        config
+    }
+
+    fn diagnostic(moniker: &str, justification: &str) -> Diagnostic {
+        Diagnostic {
+            DiagnosticType: "BestPractices".to_owned(),
+            Severity: "Warning".to_owned(),
+            ElementType: "Class".to_owned(),
+            Path: "dynamics://Class/A".to_owned(),
+            Moniker: moniker.to_owned(),
+            Message: String::new(),
+            Justification: justification.to_owned(),
+        }
+    }
+
+    #[test]
+    fn lists_must_fix_rules_of_the_model() {
+        let config = config();
+        assert_eq!(
+            Policy::must_fix(&config, "MyModel"),
+            ["BPObsolete", "BPUnusedMethod"]
+        );
+        assert_eq!(
+            Policy::must_fix(&config, "Other"),
+            ["BPMissingLabel", "BPUnusedMethod"]
+        );
+    }
+
+    #[test]
+    fn clears_justifications_of_must_fix_diagnostics() {
+        let mut data = [
+            diagnostic("BPUnusedMethod", "Called by reflection"),
+            diagnostic("BPMissingLabel", "Not shown to users"),
+            diagnostic("BPObsolete", ""),
+        ];
+        assert_eq!(clear_must_fix(&config(), "MyModel", &mut data), 1);
+        assert_eq!(data[0].Justification, "");
+        assert_eq!(data[1].Justification, "Not shown to users");
+    }
+}
@@ -5,7 +5,7 @@ use std::{fs, path::Path};
 use serde::{Deserialize, Serialize};
 use serde_xml_rs::from_str;
 
-use crate::config::Config;
+use crate::{config::Config, policy::Policy};
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct Diagnostics {
@@ -33,10 +33,21 @@ pub struct Diagnostic {
     pub Justification: String,
 }
 
+impl Diagnostic {
+    /// Suppressions are tied to a diagnostic by path and moniker.
+    pub fn same_as(&self, other: &Diagnostic) -> bool {
+        self.Path == other.Path && self.Moniker == other.Moniker
+    }
+}
+
 pub fn read_diagnostics(config: &Config, model: &String) -> Result<Vec<Diagnostic>, String> {
-    let modelsPath = Path::new(&config.modelpath);
+    let modelsPath = Path::new(config.modelpath(model));
     if !modelsPath.exists() {
-        return Err("Base model path in config doesn't exist".to_owned());
+        // Models with their own path are only checked when they are opened.
+        return match config.modelpath(model) == config.modelpath {
+            true => Err("Base model path in config doesn't exist".to_owned()),
+            false => Err(format!("Model path for {model} in config doesn't exist")),
+        };
     }
     let modelPath = modelsPath.join(&model);
     if !modelPath.exists() {
@@ -57,3 +68,18 @@ pub fn read_diagnostics(config: &Config, model: &String) -> Result<Vec<Diagnosti
     };
     Ok(diags.Items.Diagnostic)
 }
+
+/// Diagnostics as they are presented for justification: informational
+/// messages can't be suppressed and auto-ignored rules and severities the
+/// model doesn't show are hidden, so they are left out.
+pub fn read_actionable_diagnostics(
+    config: &Config,
+    model: &String,
+) -> Result<Vec<Diagnostic>, String> {
+    Ok(read_diagnostics(config, model)?
+        .into_iter()
+        .filter(|d| d.Severity != "Informational")
+        .filter(|d| config.shows_severity(model, &d.Severity))
+        .filter(|d| Policy::of(config, model, &d.Moniker) != Policy::AutoIgnore)
+        .collect())
+}
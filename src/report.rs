@@ -0,0 +1,167 @@
+use std::path::PathBuf;
+
+use crate::{
+    annotations::{to_azure, to_github},
+    catalogue::Catalogue,
+    config::Config,
+    html::to_html,
+    junit::to_junit,
+    markdown::to_markdown,
+    policy::Policy,
+    read::{read_actionable_diagnostics, Diagnostic},
+    resolve::{metadata_dir, resolve, Location},
+    sarif::to_sarif,
+    write::read_suppressions,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Sarif,
+    Junit,
+    Html,
+    Markdown,
+    Azure,
+    Github,
+}
+
+impl ReportFormat {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "sarif" => Ok(Self::Sarif),
+            "junit" => Ok(Self::Junit),
+            "html" => Ok(Self::Html),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "azure" => Ok(Self::Azure),
+            "github" => Ok(Self::Github),
+            _ => Err(format!("Unknown report format: {name}")),
+        }
+    }
+
+    /// File extension for reports written to a file.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Sarif => "sarif",
+            Self::Junit => "xml",
+            Self::Html => "html",
+            Self::Markdown => "md",
+            Self::Azure | Self::Github => "txt",
+        }
+    }
+}
+
+/// The diagnostics of a model together with its existing suppressions.
+pub struct Report {
+    pub model: String,
+    pub diagnostics: Vec<Diagnostic>,
+    pub suppressions: Vec<Diagnostic>,
+    pub metadata_dir: PathBuf,
+    pub catalogue: Catalogue,
+    /// Monikers of must-fix rules, whose diagnostics count as neither
+    /// suppressed nor justified.
+    pub must_fix: Vec<String>,
+}
+
+impl Report {
+    pub fn read(config: &Config, model: &String) -> Result<Self, String> {
+        Ok(Self {
+            model: model.clone(),
+            diagnostics: read_actionable_diagnostics(config, model)?,
+            suppressions: read_suppressions(config, model)?,
+            metadata_dir: metadata_dir(config, model),
+            catalogue: Catalogue::new(config),
+            must_fix: Policy::must_fix(config, model),
+        })
+    }
+
+    /// The diagnostic's entry in the suppression file, unless it must be
+    /// fixed: then the entry doesn't count, as in `check`.
+    pub fn suppression(&self, diagnostic: &Diagnostic) -> Option<&Diagnostic> {
+        if self.must_fix.contains(&diagnostic.Moniker) {
+            return None;
+        }
+        self.suppressions.iter().find(|s| s.same_as(diagnostic))
+    }
+
+    /// The justification entered for the diagnostic, falling back to the one
+    /// already in the suppression file. Must-fix diagnostics have none.
+    pub fn justification<'a>(&'a self, diagnostic: &'a Diagnostic) -> Option<&'a str> {
+        if self.must_fix.contains(&diagnostic.Moniker) {
+            return None;
+        }
+        if !diagnostic.Justification.is_empty() {
+            return Some(&diagnostic.Justification);
+        }
+        self.suppression(diagnostic)
+            .map(|s| s.Justification.as_str())
+            .filter(|j| !j.is_empty())
+    }
+
+    /// The metadata xml file and line the diagnostic is about.
+    pub fn location(&self, diagnostic: &Diagnostic) -> Option<Location> {
+        resolve(&self.metadata_dir, &diagnostic.Path)
+    }
+
+    /// Monikers in order of first appearance.
+    pub fn monikers(&self) -> Vec<&str> {
+        let mut monikers: Vec<&str> = vec![];
+        for d in &self.diagnostics {
+            if !monikers.contains(&d.Moniker.as_str()) {
+                monikers.push(&d.Moniker);
+            }
+        }
+        monikers
+    }
+}
+
+pub fn render_report(report: &Report, format: ReportFormat) -> Result<String, String> {
+    match format {
+        ReportFormat::Sarif => to_sarif(report),
+        ReportFormat::Junit => to_junit(report),
+        ReportFormat::Html => to_html(report),
+        ReportFormat::Markdown => to_markdown(report),
+        ReportFormat::Azure => to_azure(report),
+        ReportFormat::Github => to_github(report),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(moniker: &str, justification: &str) -> Diagnostic {
+        Diagnostic {
+            DiagnosticType: "BestPractices".to_owned(),
+            Severity: "Warning".to_owned(),
+            ElementType: "Class".to_owned(),
+            Path: "dynamics://Class/A".to_owned(),
+            Moniker: moniker.to_owned(),
+            Message: String::new(),
+            Justification: justification.to_owned(),
+        }
+    }
+
+    #[test]
+    fn must_fix_diagnostics_are_not_suppressed() {
+        let report = Report {
+            model: "MyModel".to_owned(),
+            diagnostics: vec![
+                diagnostic("BPUnusedMethod", "Entered"),
+                diagnostic("BPMissingLabel", ""),
+            ],
+            suppressions: vec![
+                diagnostic("BPUnusedMethod", "In file"),
+                diagnostic("BPMissingLabel", "In file"),
+            ],
+            metadata_dir: PathBuf::new(),
+            catalogue: Catalogue::default(),
+            must_fix: vec!["BPUnusedMethod".to_owned()],
+        };
+        let [must_fix, allowed] = &report.diagnostics[..] else {
+            unreachable!()
+        };
+        assert!(report.suppression(must_fix).is_none());
+        assert_eq!(report.justification(must_fix), None);
+        assert!(report.suppression(allowed).is_some());
+        assert_eq!(report.justification(allowed), Some("In file"));
+    }
+}
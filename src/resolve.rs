@@ -0,0 +1,141 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::config::Config;
+
+/// Folder holding the metadata xml files of a model, e.g.
+/// `PackagesLocalDirectory/MyModel/MyModel`.
+pub fn metadata_dir(config: &Config, model: &str) -> PathBuf {
+    Path::new(config.modelpath(model)).join(model).join(model)
+}
+
+/// Where in the metadata a diagnostic points to.
+pub struct Location {
+    pub file: PathBuf,
+    /// 1-based line of the sub element (method, field, ...) or 1 for the
+    /// element itself.
+    pub line: usize,
+}
+
+impl Location {
+    /// The file relative to the metadata folder, e.g. `AxClass/MyClass.xml:12`.
+    pub fn short(&self, metadata_dir: &Path) -> String {
+        let file = self.file.strip_prefix(metadata_dir).unwrap_or(&self.file);
+        format!("{}:{}", file.display(), self.line)
+    }
+}
+
+/// Metadata folder for the element type in a logical path. Types without a
+/// special case use `Ax<type>`, which covers e.g. `Class`, `Table`, `Form`,
+/// `Query`, `View` and their `...Extension` types. All EDT types, and all EDT
+/// extensions, share one folder.
+fn element_folder(element_type: &str) -> String {
+    let folder = match element_type {
+        "BaseEnum" => "AxEnum",
+        "ExtendedDataType" | "EDT" => "AxEdt",
+        "EDTExtension" => "AxEdtExtension",
+        t if t.starts_with("Edt") && t.ends_with("Extension") => "AxEdtExtension",
+        t if t.starts_with("Edt") => "AxEdt",
+        "DataEntity" => "AxDataEntityView",
+        "DataEntityExtension" => "AxDataEntityViewExtension",
+        "BaseEnumExtension" => "AxEnumExtension",
+        t => return format!("Ax{t}"),
+    };
+    folder.to_owned()
+}
+
+/// Maps a logical path like `dynamics://Class/MyClass/Method/foo` to the
+/// element's metadata file, e.g. `AxClass/MyClass.xml`, if it exists, and
+/// the line the method, field, ... is declared on.
+pub fn resolve(metadata_dir: &Path, path: &str) -> Option<Location> {
+    let mut parts = path.strip_prefix("dynamics://")?.split('/');
+    let element_type = parts.next()?;
+    let name = parts.next()?;
+    let file = metadata_dir
+        .join(element_folder(element_type))
+        .join(format!("{name}.xml"));
+    if !file.exists() {
+        return None;
+    }
+    let line = match parts.next_back() {
+        Some(member) => find_line(&file, member).unwrap_or(1),
+        None => 1,
+    };
+    Some(Location { file, line })
+}
+
+/// Line of the first `<Name>member</Name>` in the file.
+fn find_line(file: &Path, member: &str) -> Option<usize> {
+    let xml = fs::read_to_string(file).ok()?;
+    let tag = format!("<Name>{member}</Name>");
+    xml.lines().position(|l| l.contains(&tag)).map(|i| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    /// A metadata folder of its own per test, as tests run in parallel.
+    fn metadata(test: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("bp-manager resolve {test} {}", std::process::id()));
+        fs::create_dir_all(dir.join("AxClass")).unwrap();
+        fs::create_dir_all(dir.join("AxEdt")).unwrap();
+        fs::write(
+            dir.join("AxClass").join("CustFoo.xml"),
+            "<AxClass>\n\t<Name>CustFoo</Name>\n\t<Methods>\n\t\t<Method>\n\t\t\t<Name>run</Name>\n",
+        )
+        .unwrap();
+        fs::write(dir.join("AxEdt").join("CustId.xml"), "<AxEdt />\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn resolves_elements_and_members() {
+        let dir = metadata("resolves_elements_and_members");
+        let location = resolve(&dir, "dynamics://Class/CustFoo/Method/run").unwrap();
+        assert_eq!(location.file, dir.join("AxClass").join("CustFoo.xml"));
+        assert_eq!(location.line, 5);
+        assert_eq!(
+            location.short(&dir),
+            format!("{}:5", Path::new("AxClass").join("CustFoo.xml").display())
+        );
+
+        assert_eq!(resolve(&dir, "dynamics://Class/CustFoo").unwrap().line, 1);
+        // A member that isn't found points to the element.
+        assert_eq!(
+            resolve(&dir, "dynamics://Class/CustFoo/Method/missing")
+                .unwrap()
+                .line,
+            1
+        );
+        assert_eq!(
+            resolve(&dir, "dynamics://EdtString/CustId").unwrap().file,
+            dir.join("AxEdt").join("CustId.xml")
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn ignores_missing_files_and_other_paths() {
+        let dir = metadata("ignores_missing_files_and_other_paths");
+        assert!(resolve(&dir, "dynamics://Class/VendFoo").is_none());
+        assert!(resolve(&dir, "dynamics://Class").is_none());
+        assert!(resolve(&dir, "/Class/CustFoo").is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn maps_element_types_to_folders() {
+        assert_eq!(element_folder("Class"), "AxClass");
+        assert_eq!(element_folder("TableExtension"), "AxTableExtension");
+        assert_eq!(element_folder("BaseEnum"), "AxEnum");
+        assert_eq!(element_folder("EdtString"), "AxEdt");
+        assert_eq!(element_folder("EdtStringExtension"), "AxEdtExtension");
+        assert_eq!(element_folder("EDTExtension"), "AxEdtExtension");
+        assert_eq!(element_folder("DataEntity"), "AxDataEntityView");
+    }
+}
@@ -0,0 +1,224 @@
+use std::fs;
+
+use glob::Pattern;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::read::Diagnostic;
+
+/// A rule from a rules file. Every condition that is set has to match;
+/// `path` is a glob and `path_regex` a regular expression on the logical path.
+#[derive(Deserialize)]
+pub struct JustificationRule {
+    pub moniker: Option<String>,
+    pub path: Option<String>,
+    pub path_regex: Option<String>,
+    pub element_type: Option<String>,
+    pub message: Option<String>,
+    pub justification: String,
+}
+
+#[derive(Deserialize)]
+struct RulesFile {
+    #[serde(default)]
+    rule: Vec<JustificationRule>,
+}
+
+struct CompiledRule<'a> {
+    rule: &'a JustificationRule,
+    path: Option<Pattern>,
+    path_regex: Option<Regex>,
+}
+
+impl CompiledRule<'_> {
+    fn matches(&self, d: &Diagnostic) -> bool {
+        self.rule.moniker.as_ref().is_none_or(|m| *m == d.Moniker)
+            && self
+                .rule
+                .element_type
+                .as_ref()
+                .is_none_or(|t| *t == d.ElementType)
+            && self
+                .rule
+                .message
+                .as_ref()
+                .is_none_or(|m| d.Message.contains(m.as_str()))
+            && self.path.as_ref().is_none_or(|p| p.matches(&d.Path))
+            && self.path_regex.as_ref().is_none_or(|r| r.is_match(&d.Path))
+    }
+}
+
+pub fn read_rules(file: &str) -> Result<Vec<JustificationRule>, String> {
+    let toml = match fs::read_to_string(file) {
+        Ok(toml) => toml,
+        Err(_) => return Err(format!("Could not read rules file {file}")),
+    };
+    match toml::from_str::<RulesFile>(&toml) {
+        Ok(rules) => Ok(rules.rule),
+        Err(e) => Err(format!(
+            "Could not parse rules file {file}: {}",
+            e.message()
+        )),
+    }
+}
+
+/// Sets the justification of the first matching rule on every diagnostic
+/// that isn't justified yet. Returns the number of diagnostics justified.
+pub fn apply_rules(
+    rules: &[JustificationRule],
+    data: &mut [Diagnostic],
+    suppressions: &[Diagnostic],
+) -> Result<usize, String> {
+    let mut compiled = vec![];
+    for rule in rules {
+        let path = match &rule.path {
+            Some(p) => match Pattern::new(p) {
+                Ok(p) => Some(p),
+                Err(_) => return Err(format!("Invalid path glob: {p}")),
+            },
+            None => None,
+        };
+        let path_regex = match &rule.path_regex {
+            Some(r) => match Regex::new(r) {
+                Ok(r) => Some(r),
+                Err(_) => return Err(format!("Invalid path regex: {r}")),
+            },
+            None => None,
+        };
+        compiled.push(CompiledRule {
+            rule,
+            path,
+            path_regex,
+        });
+    }
+
+    let mut count = 0;
+    for d in data.iter_mut() {
+        let justified = !d.Justification.is_empty()
+            || suppressions
+                .iter()
+                .any(|s| s.same_as(d) && !s.Justification.is_empty());
+        if justified {
+            continue;
+        }
+        if let Some(rule) = compiled.iter().find(|r| r.matches(d)) {
+            d.Justification = rule.rule.justification.clone();
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(path: &str, moniker: &str, justification: &str) -> Diagnostic {
+        Diagnostic {
+            DiagnosticType: "BestPractices".to_owned(),
+            Severity: "Warning".to_owned(),
+            ElementType: "Class".to_owned(),
+            Path: path.to_owned(),
+            Moniker: moniker.to_owned(),
+            Message: "Method is not used".to_owned(),
+            Justification: justification.to_owned(),
+        }
+    }
+
+    fn rule(justification: &str) -> JustificationRule {
+        JustificationRule {
+            moniker: None,
+            path: None,
+            path_regex: None,
+            element_type: None,
+            message: None,
+            justification: justification.to_owned(),
+        }
+    }
+
+    #[test]
+    fn requires_all_conditions() {
+        let rules = [JustificationRule {
+            moniker: Some("BPUnusedMethod".to_owned()),
+            path: Some("dynamics://Class/Cust*".to_owned()),
+            element_type: Some("Class".to_owned()),
+            message: Some("not used".to_owned()),
+            ..rule("Called by reflection")
+        }];
+        let mut data = [
+            diagnostic("dynamics://Class/CustFoo/Method/a", "BPUnusedMethod", ""),
+            diagnostic("dynamics://Class/VendFoo/Method/a", "BPUnusedMethod", ""),
+            diagnostic("dynamics://Class/CustFoo/Method/a", "BPOther", ""),
+        ];
+        assert_eq!(apply_rules(&rules, &mut data, &[]), Ok(1));
+        assert_eq!(data[0].Justification, "Called by reflection");
+        assert_eq!(data[1].Justification, "");
+        assert_eq!(data[2].Justification, "");
+
+        let rules = [JustificationRule {
+            path_regex: Some("^dynamics://Class/Cust".to_owned()),
+            message: Some("obsolete".to_owned()),
+            ..rule("Called by reflection")
+        }];
+        assert_eq!(apply_rules(&rules, &mut data, &[]), Ok(0));
+    }
+
+    #[test]
+    fn uses_the_first_matching_rule() {
+        let rules = [
+            JustificationRule {
+                path_regex: Some("Foo".to_owned()),
+                ..rule("First")
+            },
+            rule("Second"),
+        ];
+        let mut data = [
+            diagnostic("dynamics://Class/CustFoo", "BPUnusedMethod", ""),
+            diagnostic("dynamics://Class/CustBar", "BPUnusedMethod", ""),
+        ];
+        assert_eq!(apply_rules(&rules, &mut data, &[]), Ok(2));
+        assert_eq!(data[0].Justification, "First");
+        assert_eq!(data[1].Justification, "Second");
+    }
+
+    #[test]
+    fn skips_justified_diagnostics() {
+        let rules = [rule("From rule")];
+        let mut data = [
+            diagnostic("dynamics://Class/A", "BPUnusedMethod", "By hand"),
+            diagnostic("dynamics://Class/B", "BPUnusedMethod", ""),
+            diagnostic("dynamics://Class/C", "BPUnusedMethod", ""),
+        ];
+        let suppressions = [diagnostic(
+            "dynamics://Class/B",
+            "BPUnusedMethod",
+            "In file",
+        )];
+        assert_eq!(apply_rules(&rules, &mut data, &suppressions), Ok(1));
+        assert_eq!(data[0].Justification, "By hand");
+        assert_eq!(data[1].Justification, "");
+        assert_eq!(data[2].Justification, "From rule");
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        let mut data = [diagnostic("dynamics://Class/A", "BPUnusedMethod", "")];
+        let rules = [JustificationRule {
+            path: Some("[Cust".to_owned()),
+            ..rule("x")
+        }];
+        assert_eq!(
+            apply_rules(&rules, &mut data, &[]),
+            Err("Invalid path glob: [Cust".to_owned())
+        );
+        let rules = [JustificationRule {
+            path_regex: Some("(Cust".to_owned()),
+            ..rule("x")
+        }];
+        assert_eq!(
+            apply_rules(&rules, &mut data, &[]),
+            Err("Invalid path regex: (Cust".to_owned())
+        );
+        assert_eq!(data[0].Justification, "");
+    }
+}
@@ -0,0 +1,92 @@
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::{read::Diagnostic, report::Report};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+fn level(severity: &str) -> &'static str {
+    match severity {
+        "Error" => "error",
+        "Warning" => "warning",
+        _ => "note",
+    }
+}
+
+fn file_uri(file: &Path) -> String {
+    let path = file.to_string_lossy().replace('\\', "/");
+    match path.starts_with('/') {
+        true => format!("file://{path}"),
+        false => format!("file:///{path}"),
+    }
+}
+
+fn rule(report: &Report, moniker: &str) -> Value {
+    let mut rule = json!({ "id": moniker });
+    if let Some(info) = report.catalogue.get(moniker) {
+        rule["shortDescription"] = json!({ "text": info.title });
+        if !info.explanation.is_empty() {
+            rule["fullDescription"] = json!({ "text": info.explanation });
+        }
+        if !info.fix.is_empty() {
+            rule["help"] = json!({ "text": info.fix });
+        }
+    }
+    rule
+}
+
+fn result(report: &Report, rules: &[&str], d: &Diagnostic) -> Value {
+    let name = d.Path.rsplit('/').next().unwrap_or(&d.Path);
+    let mut result = json!({
+        "ruleId": d.Moniker,
+        "ruleIndex": rules.iter().position(|r| *r == d.Moniker),
+        "level": level(&d.Severity),
+        "message": { "text": d.Message },
+        "locations": [{
+            "logicalLocations": [{
+                "name": name,
+                "fullyQualifiedName": d.Path,
+            }]
+        }],
+    });
+    if let Some(location) = report.location(d) {
+        result["locations"][0]["physicalLocation"] = json!({
+            "artifactLocation": { "uri": file_uri(&location.file) },
+            "region": { "startLine": location.line },
+        });
+    }
+    if let Some(justification) = report.justification(d) {
+        result["suppressions"] = json!([{
+            "kind": "external",
+            "status": "accepted",
+            "justification": justification,
+        }]);
+    }
+    result
+}
+
+/// Converts the report into a SARIF 2.1.0 log with one run.
+pub fn to_sarif(report: &Report) -> Result<String, String> {
+    let rules = report.monikers();
+    let results: Vec<Value> = report
+        .diagnostics
+        .iter()
+        .map(|d| result(report, &rules, d))
+        .collect();
+    let log = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "D365 Best Practices",
+                    "rules": rules.iter().map(|r| rule(report, r)).collect::<Vec<_>>(),
+                }
+            },
+            "automationDetails": { "id": format!("{}/", report.model) },
+            "results": results,
+        }]
+    });
+    serde_json::to_string_pretty(&log).map_err(|_| "Could not serialize sarif".to_owned())
+}
@@ -0,0 +1,37 @@
+use serde::Deserialize;
+
+use crate::read::Diagnostic;
+
+/// A reusable justification from the config. Without a moniker it is offered
+/// for every diagnostic.
+#[derive(Deserialize, Clone)]
+pub struct Snippet {
+    pub text: String,
+    pub moniker: Option<String>,
+}
+
+impl Snippet {
+    pub fn applies_to(&self, d: &Diagnostic) -> bool {
+        self.moniker.as_ref().is_none_or(|m| *m == d.Moniker)
+    }
+
+    /// Fills in the placeholders from the diagnostic:
+    /// `{element}` (e.g. `MyClass`), `{type}` (e.g. `Class`), `{name}` (last
+    /// part of the path, e.g. `foo`), `{path}` and `{moniker}`.
+    pub fn expand(&self, d: &Diagnostic) -> String {
+        let mut parts = d
+            .Path
+            .strip_prefix("dynamics://")
+            .unwrap_or(&d.Path)
+            .split('/');
+        let element_type = parts.next().unwrap_or("");
+        let element = parts.next().unwrap_or("");
+        let name = d.Path.rsplit('/').next().unwrap_or("");
+        self.text
+            .replace("{element}", element)
+            .replace("{type}", element_type)
+            .replace("{name}", name)
+            .replace("{path}", &d.Path)
+            .replace("{moniker}", &d.Moniker)
+    }
+}
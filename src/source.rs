@@ -0,0 +1,186 @@
+use std::{fs, path::Path};
+
+use quick_xml::{events::Event, Reader};
+
+/// A piece of X++ source from a metadata file: the class declaration or a
+/// method.
+pub struct SourceBlock {
+    pub name: String,
+    pub code: String,
+}
+
+impl SourceBlock {
+    /// The code as shown, without leading and trailing empty lines and with
+    /// tabs expanded.
+    pub fn lines(&self) -> impl Iterator<Item = String> + '_ {
+        self.code
+            .trim_matches('\n')
+            .lines()
+            .map(|l| l.replace('\t', "    "))
+    }
+}
+
+/// Extracts the declaration and the method sources from an Ax*.xml file.
+pub fn read_source(file: &Path) -> Result<Vec<SourceBlock>, String> {
+    let xml = match fs::read_to_string(file) {
+        Ok(xml) => xml,
+        Err(_) => return Err(format!("Could not read {}", file.display())),
+    };
+    let mut reader = Reader::from_str(&xml);
+    let mut blocks = vec![];
+    let mut stack: Vec<String> = vec![];
+    let mut method_name = String::new();
+    loop {
+        let text = match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                stack.push(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+                continue;
+            }
+            Ok(Event::End(_)) => {
+                stack.pop();
+                continue;
+            }
+            Ok(Event::Text(t)) => t.unescape().map(|t| t.into_owned()).unwrap_or_default(),
+            Ok(Event::CData(c)) => String::from_utf8_lossy(&c).into_owned(),
+            Ok(Event::Eof) => break,
+            Ok(_) => continue,
+            Err(_) => return Err(format!("Could not parse {}", file.display())),
+        };
+        let parent = stack.len().checked_sub(2).map(|i| stack[i].as_str());
+        match (parent, stack.last().map(|s| s.as_str())) {
+            (Some("Method"), Some("Name")) => method_name = text,
+            (Some("Method"), Some("Source")) if !text.trim().is_empty() => {
+                blocks.push(SourceBlock {
+                    name: method_name.clone(),
+                    code: text,
+                })
+            }
+            (_, Some("Declaration")) if !text.trim().is_empty() => blocks.push(SourceBlock {
+                name: "classDeclaration".to_owned(),
+                code: text,
+            }),
+            _ => {}
+        }
+    }
+    Ok(blocks)
+}
+
+/// The method a logical path points to, e.g. `foo` for
+/// `dynamics://Class/MyClass/Method/foo`.
+pub fn method_name(path: &str) -> Option<&str> {
+    let mut parts = path.rsplit('/');
+    let name = parts.next()?;
+    (parts.next()? == "Method").then_some(name)
+}
+
+/// Line of the method's block when the blocks are listed as a `// name`
+/// header, the code lines and an empty line each.
+pub fn method_offset(blocks: &[SourceBlock], method: &str) -> Option<usize> {
+    let mut offset = 0;
+    for block in blocks {
+        if block.name == method {
+            return Some(offset);
+        }
+        offset += block.lines().count() + 2;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    const CLASS: &str = "<?xml version=\"1.0\" encoding=\"utf-8\"?>
+<AxClass xmlns:i=\"http://www.w3.org/2001/XMLSchema-instance\">
+\t<Name>CustFoo</Name>
+\t<SourceCode>
+\t\t<Declaration><![CDATA[
+class CustFoo
+{
+}
+]]></Declaration>
+\t\t<Methods>
+\t\t\t<Method>
+\t\t\t\t<Name>run</Name>
+\t\t\t\t<Source><![CDATA[
+\tpublic void run()
+\t{
+\t\tinfo(\"a &amp; b\");
+\t}
+
+]]></Source>
+\t\t\t</Method>
+\t\t\t<Method>
+\t\t\t\t<Name>empty</Name>
+\t\t\t\t<Source><![CDATA[]]></Source>
+\t\t\t</Method>
+\t\t\t<Method>
+\t\t\t\t<Name>cancel</Name>
+\t\t\t\t<Source><![CDATA[
+\tpublic void cancel()
+\t{
+\t}
+]]></Source>
+\t\t\t</Method>
+\t\t</Methods>
+\t</SourceCode>
+</AxClass>
+";
+
+    fn read(xml: &str, test: &str) -> Result<Vec<SourceBlock>, String> {
+        let file = env::temp_dir().join(format!(
+            "bp-manager source {test} {}.xml",
+            std::process::id()
+        ));
+        fs::write(&file, xml).unwrap();
+        let blocks = read_source(&file);
+        fs::remove_file(file).unwrap();
+        blocks
+    }
+
+    #[test]
+    fn reads_the_declaration_and_methods() {
+        let blocks = read(CLASS, "reads_the_declaration_and_methods").unwrap();
+        let names: Vec<&str> = blocks.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, ["classDeclaration", "run", "cancel"]);
+        assert_eq!(
+            blocks[1].lines().collect::<Vec<_>>(),
+            [
+                "    public void run()",
+                "    {",
+                "        info(\"a &amp; b\");",
+                "    }"
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_files_it_cannot_read() {
+        let error = read(
+            "<AxClass><Name>CustFoo</AxClass>",
+            "reports_files_it_cannot_read",
+        );
+        assert!(error.is_err());
+        assert!(read_source(Path::new("missing.xml")).is_err());
+    }
+
+    #[test]
+    fn finds_the_highlighted_method() {
+        assert_eq!(
+            method_name("dynamics://Class/CustFoo/Method/run"),
+            Some("run")
+        );
+        assert_eq!(method_name("dynamics://Class/CustFoo"), None);
+        assert_eq!(method_name("dynamics://Table/CustTable/Field/run"), None);
+
+        let blocks = read(CLASS, "finds_the_highlighted_method").unwrap();
+        // classDeclaration takes a header, three lines and an empty line, run
+        // a header, four lines and an empty line.
+        assert_eq!(method_offset(&blocks, "classDeclaration"), Some(0));
+        assert_eq!(method_offset(&blocks, "run"), Some(5));
+        assert_eq!(method_offset(&blocks, "cancel"), Some(11));
+        assert_eq!(method_offset(&blocks, "empty"), None);
+    }
+}
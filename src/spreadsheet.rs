@@ -0,0 +1,77 @@
+#![allow(non_snake_case)]
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::read::Diagnostic;
+
+#[derive(Debug, Serialize)]
+struct ExportRow<'a> {
+    Moniker: &'a str,
+    Severity: &'a str,
+    Path: &'a str,
+    ElementType: &'a str,
+    Message: &'a str,
+    Justification: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportRow {
+    Moniker: String,
+    Path: String,
+    #[serde(default)]
+    Justification: String,
+}
+
+pub fn export_csv(data: &[Diagnostic], file: &Path) -> Result<(), String> {
+    let mut writer = match csv::Writer::from_path(file) {
+        Ok(w) => w,
+        Err(_) => return Err(format!("Could not create {}", file.display())),
+    };
+    for d in data {
+        let row = ExportRow {
+            Moniker: &d.Moniker,
+            Severity: &d.Severity,
+            Path: &d.Path,
+            ElementType: &d.ElementType,
+            Message: &d.Message,
+            Justification: &d.Justification,
+        };
+        if writer.serialize(row).is_err() {
+            return Err("Could not write csv row".to_owned());
+        }
+    }
+    match writer.flush() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("Could not write {}", file.display())),
+    }
+}
+
+/// Applies justifications from a (possibly hand edited) csv file onto `data`.
+/// Rows are matched on Path and Moniker; rows without a match are ignored.
+/// Returns the number of diagnostics that were updated.
+pub fn import_csv(data: &mut [Diagnostic], file: &Path) -> Result<usize, String> {
+    let mut reader = match csv::Reader::from_path(file) {
+        Ok(r) => r,
+        Err(_) => return Err(format!("Could not read {}", file.display())),
+    };
+    let mut updated = 0;
+    for row in reader.deserialize() {
+        let row: ImportRow = match row {
+            Ok(r) => r,
+            Err(_) => return Err(format!("Could not parse {}", file.display())),
+        };
+        if let Some(d) = data
+            .iter_mut()
+            .find(|d| d.Path == row.Path && d.Moniker == row.Moniker)
+        {
+            let justification = row.Justification.trim();
+            if d.Justification != justification {
+                d.Justification = justification.to_owned();
+                updated += 1;
+            }
+        }
+    }
+    Ok(updated)
+}
@@ -0,0 +1,190 @@
+use std::{fs, io::ErrorKind, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::read::Diagnostic;
+
+const STATE_FILE: &str = "./bp-manager.state.toml";
+
+/// What the app remembers between runs, kept next to `config.toml`.
+#[derive(Deserialize, Serialize, Default, Clone, PartialEq, Debug)]
+pub struct State {
+    pub theme: Option<String>,
+    pub profile: Option<String>,
+    pub model: Option<String>,
+    /// Row selected in the model's diagnostics.
+    pub selected: Option<usize>,
+    pub owner_filter: Option<String>,
+    /// The review view, which only shows proposed justifications, was open.
+    #[serde(default)]
+    pub review: bool,
+    /// Justifications entered, changed or cleared but not written to the
+    /// suppression file yet.
+    #[serde(default)]
+    pub drafts: Vec<Draft>,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+pub struct Draft {
+    pub model: String,
+    pub path: String,
+    pub moniker: String,
+    pub justification: String,
+}
+
+impl Draft {
+    pub fn is_for(&self, model: &str, d: &Diagnostic) -> bool {
+        self.model == model && self.path == d.Path && self.moniker == d.Moniker
+    }
+}
+
+impl State {
+    /// Puts the model's drafts back into its diagnostics.
+    pub fn restore_drafts(&self, model: &str, data: &mut [Diagnostic]) {
+        for draft in &self.drafts {
+            if let Some(d) = data.iter_mut().find(|d| draft.is_for(model, d)) {
+                d.Justification = draft.justification.clone();
+            }
+        }
+    }
+
+    /// Replaces the model's drafts with the justifications that differ from
+    /// `loaded`, the justifications as they were loaded or last written.
+    pub fn set_drafts(&mut self, model: &str, data: &[Diagnostic], loaded: &[String]) {
+        self.drafts.retain(|d| d.model != model);
+        for (d, loaded) in data.iter().zip(loaded) {
+            if d.Justification != *loaded {
+                self.drafts.push(Draft {
+                    model: model.to_owned(),
+                    path: d.Path.clone(),
+                    moniker: d.Moniker.clone(),
+                    justification: d.Justification.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// The saved state, or the default if there is none yet. A file that can't
+/// be read is an error, so it isn't overwritten with an empty state.
+pub fn load_state() -> Result<State, String> {
+    read_state(Path::new(STATE_FILE))
+}
+
+pub fn save_state(state: &State) -> Result<(), String> {
+    write_state(Path::new(STATE_FILE), state)
+}
+
+fn read_state(file: &Path) -> Result<State, String> {
+    let toml = match fs::read_to_string(file) {
+        Ok(toml) => toml,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(State::default()),
+        Err(_) => return Err(format!("Could not read {}", file.display())),
+    };
+    match toml::from_str(&toml) {
+        Ok(state) => Ok(state),
+        Err(_) => Err(format!(
+            "Could not parse {}, the session isn't saved",
+            file.display()
+        )),
+    }
+}
+
+/// Writes to a temporary file first, so a dropped session can't leave a
+/// half written state file behind.
+fn write_state(file: &Path, state: &State) -> Result<(), String> {
+    let toml = match toml::to_string(state) {
+        Ok(toml) => toml,
+        Err(_) => return Err("Could not serialize state".to_owned()),
+    };
+    let temp = file.with_extension("toml.tmp");
+    match fs::write(&temp, toml).and_then(|_| fs::rename(&temp, file)) {
+        Ok(()) => Ok(()),
+        Err(_) => Err(format!("Could not write {}", file.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn diagnostic(path: &str, justification: &str) -> Diagnostic {
+        Diagnostic {
+            DiagnosticType: "BestPractices".to_owned(),
+            Severity: "Warning".to_owned(),
+            ElementType: "Class".to_owned(),
+            Path: path.to_owned(),
+            Moniker: "BPUnusedMethod".to_owned(),
+            Message: String::new(),
+            Justification: justification.to_owned(),
+        }
+    }
+
+    fn state_file(test: &str) -> std::path::PathBuf {
+        env::temp_dir().join(format!(
+            "bp-manager state {test} {}.toml",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn round_trips_the_state() {
+        let file = state_file("round_trips_the_state");
+        assert_eq!(read_state(&file), Ok(State::default()));
+        let mut state = State {
+            theme: Some("light".to_owned()),
+            profile: Some("dev".to_owned()),
+            model: Some("MyModel".to_owned()),
+            selected: Some(3),
+            owner_filter: Some("@sales".to_owned()),
+            review: true,
+            drafts: vec![],
+        };
+        state.set_drafts(
+            "MyModel",
+            &[diagnostic("dynamics://Class/A", "Called by reflection")],
+            &[String::new()],
+        );
+        write_state(&file, &state).unwrap();
+        assert_eq!(read_state(&file), Ok(state));
+        assert!(!file.with_extension("toml.tmp").exists());
+        fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn keeps_a_state_file_it_cannot_parse() {
+        let file = state_file("keeps_a_state_file_it_cannot_parse");
+        fs::write(&file, "drafts = [{ model = ").unwrap();
+        assert!(read_state(&file).is_err());
+        fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn restores_changed_and_cleared_justifications() {
+        let loaded = ["".to_owned(), "Proposed".to_owned(), "Same".to_owned()];
+        let items = [
+            diagnostic("dynamics://Class/A", "New"),
+            diagnostic("dynamics://Class/B", ""),
+            diagnostic("dynamics://Class/C", "Same"),
+        ];
+        let mut state = State::default();
+        state.set_drafts("Other", &items, &loaded);
+        state.set_drafts("MyModel", &items, &loaded);
+        state.set_drafts("MyModel", &items, &loaded);
+        assert_eq!(state.drafts.len(), 4);
+
+        let mut data = [
+            diagnostic("dynamics://Class/A", ""),
+            diagnostic("dynamics://Class/B", "Proposed"),
+            diagnostic("dynamics://Class/C", "Same"),
+            diagnostic("dynamics://Class/D", ""),
+        ];
+        state.restore_drafts("MyModel", &mut data);
+        assert_eq!(data[0].Justification, "New");
+        assert_eq!(data[1].Justification, "");
+        assert_eq!(data[2].Justification, "Same");
+        assert_eq!(data[3].Justification, "");
+    }
+}
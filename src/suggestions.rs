@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+
+use crate::{config::Config, write::read_suppressions};
+
+/// Justifications used before, per moniker, with the number of times each
+/// was used. Most used first.
+pub type Suggestions = HashMap<String, Vec<(String, usize)>>;
+
+/// Collects the justifications from the suppression files of all configured
+/// models. Models without a readable suppression file are skipped.
+pub fn collect_suggestions(config: &Config) -> Suggestions {
+    let mut counts: HashMap<String, HashMap<String, usize>> = HashMap::new();
+    for model in &config.models {
+        let Ok(suppressions) = read_suppressions(config, model) else {
+            continue;
+        };
+        for s in suppressions {
+            let justification = s.Justification.trim();
+            if justification.is_empty() {
+                continue;
+            }
+            *counts
+                .entry(s.Moniker)
+                .or_default()
+                .entry(justification.to_owned())
+                .or_insert(0) += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(moniker, texts)| {
+            let mut texts: Vec<(String, usize)> = texts.into_iter().collect();
+            texts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            (moniker, texts)
+        })
+        .collect()
+}
@@ -0,0 +1,219 @@
+use std::env;
+
+use ratatui::style::{palette::tailwind, Color};
+use serde::Deserialize;
+
+use crate::config::Config;
+
+/// Colours of the app. Colours left out of a `[[themes]]` entry in the
+/// config are taken from the default theme, but the name has to be set.
+#[derive(Deserialize, Clone)]
+#[serde(default = "Theme::unnamed")]
+pub struct Theme {
+    pub name: String,
+    pub buffer_bg: Color,
+    pub header_bg: Color,
+    pub header_fg: Color,
+    pub row_fg: Color,
+    pub selected_style_fg: Color,
+    pub normal_row_color: Color,
+    pub alt_row_color: Color,
+    pub footer_border_color: Color,
+    /// Text being edited and the highlighted method in the source pane.
+    pub edit_fg: Color,
+    /// Rows with justification problems.
+    pub error_fg: Color,
+    /// Rows with an expired suppression.
+    pub expired_fg: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark("blue", &tailwind::BLUE)
+    }
+}
+
+impl Theme {
+    /// The default colours without a name, so a config theme missing its
+    /// name doesn't replace `blue`.
+    fn unnamed() -> Self {
+        Self {
+            name: String::new(),
+            ..Self::default()
+        }
+    }
+
+    fn dark(name: &str, color: &tailwind::Palette) -> Self {
+        Self {
+            name: name.to_owned(),
+            buffer_bg: tailwind::SLATE.c950,
+            header_bg: color.c900,
+            header_fg: tailwind::SLATE.c200,
+            row_fg: tailwind::SLATE.c200,
+            selected_style_fg: color.c400,
+            normal_row_color: tailwind::SLATE.c950,
+            alt_row_color: tailwind::SLATE.c900,
+            footer_border_color: color.c400,
+            edit_fg: Color::Yellow,
+            error_fg: Color::Red,
+            expired_fg: Color::Magenta,
+        }
+    }
+
+    fn light() -> Self {
+        Self {
+            name: "light".to_owned(),
+            buffer_bg: tailwind::SLATE.c50,
+            header_bg: tailwind::BLUE.c200,
+            header_fg: tailwind::SLATE.c900,
+            row_fg: tailwind::SLATE.c900,
+            selected_style_fg: tailwind::BLUE.c700,
+            normal_row_color: tailwind::SLATE.c50,
+            alt_row_color: tailwind::SLATE.c200,
+            footer_border_color: tailwind::BLUE.c700,
+            edit_fg: tailwind::AMBER.c800,
+            error_fg: tailwind::RED.c700,
+            expired_fg: tailwind::FUCHSIA.c700,
+        }
+    }
+
+    fn high_contrast() -> Self {
+        Self {
+            name: "high-contrast".to_owned(),
+            buffer_bg: Color::Black,
+            header_bg: Color::White,
+            header_fg: Color::Black,
+            row_fg: Color::White,
+            selected_style_fg: Color::LightYellow,
+            normal_row_color: Color::Black,
+            alt_row_color: Color::Black,
+            footer_border_color: Color::White,
+            edit_fg: Color::LightYellow,
+            error_fg: Color::LightRed,
+            expired_fg: Color::LightMagenta,
+        }
+    }
+
+    /// The terminal's own colours, for `NO_COLOR`.
+    fn no_color() -> Self {
+        Self {
+            name: "no-color".to_owned(),
+            buffer_bg: Color::Reset,
+            header_bg: Color::Reset,
+            header_fg: Color::Reset,
+            row_fg: Color::Reset,
+            selected_style_fg: Color::Reset,
+            normal_row_color: Color::Reset,
+            alt_row_color: Color::Reset,
+            footer_border_color: Color::Reset,
+            edit_fg: Color::Reset,
+            error_fg: Color::Reset,
+            expired_fg: Color::Reset,
+        }
+    }
+}
+
+/// The built-in themes followed by the ones from the config, which replace
+/// built-in themes of the same name. Only the terminal's colours are used
+/// when `NO_COLOR` is set.
+pub fn themes(config: &Config) -> Vec<Theme> {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    themes_for(config, no_color)
+}
+
+fn themes_for(config: &Config, no_color: bool) -> Vec<Theme> {
+    if no_color {
+        return vec![Theme::no_color()];
+    }
+    let mut themes = vec![
+        Theme::default(),
+        Theme::dark("emerald", &tailwind::EMERALD),
+        Theme::dark("indigo", &tailwind::INDIGO),
+        Theme::dark("red", &tailwind::RED),
+        Theme::high_contrast(),
+        Theme::light(),
+    ];
+    for theme in &config.themes {
+        match themes.iter_mut().find(|t| t.name == theme.name) {
+            Some(t) => *t = theme.clone(),
+            None => themes.push(theme.clone()),
+        }
+    }
+    themes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toml: &str) -> Config {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn names(themes: &[Theme]) -> Vec<&str> {
+        themes.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    #[test]
+    fn replaces_built_in_themes_by_name() {
+        let themes = themes_for(
+            &config(
+                "[[themes]]
+name = 'red'
+row_fg = '#ff0000'
+
+[[themes]]
+name = 'mine'
+",
+            ),
+            false,
+        );
+        assert_eq!(
+            names(&themes),
+            [
+                "blue",
+                "emerald",
+                "indigo",
+                "red",
+                "high-contrast",
+                "light",
+                "mine"
+            ]
+        );
+        assert_eq!(themes[3].row_fg, Color::Rgb(255, 0, 0));
+        // The rest of a replaced theme comes from the default, not the
+        // built-in theme it replaces.
+        assert_eq!(themes[3].header_bg, Theme::default().header_bg);
+    }
+
+    #[test]
+    fn takes_missing_colours_from_the_default_theme() {
+        let config = config(
+            "[[themes]]
+name = 'mine'
+row_fg = '42'
+error_fg = 'lightred'
+",
+        );
+        let theme = &config.themes[0];
+        assert_eq!(theme.row_fg, Color::Indexed(42));
+        assert_eq!(theme.error_fg, Color::LightRed);
+        let default = Theme::default();
+        assert_eq!(theme.buffer_bg, default.buffer_bg);
+        assert_eq!(theme.expired_fg, default.expired_fg);
+    }
+
+    #[test]
+    fn leaves_the_name_empty_if_missing() {
+        let config = config("[[themes]]\nrow_fg = 'red'\n");
+        assert_eq!(config.themes[0].name, "");
+        assert_eq!(themes_for(&config, false)[0].name, "blue");
+    }
+
+    #[test]
+    fn uses_the_terminal_colours_with_no_color() {
+        let themes = themes_for(&config("[[themes]]\nname = 'mine'\n"), true);
+        assert_eq!(names(&themes), ["no-color"]);
+        assert_eq!(themes[0].row_fg, Color::Reset);
+    }
+}
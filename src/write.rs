@@ -1,12 +1,20 @@
 #![allow(non_snake_case)]
 
-use std::{fs, path::Path};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use quick_xml::se::to_string;
 use serde::{Deserialize, Serialize};
 use serde_xml_rs::from_str;
 
-use crate::{config::Config, read::Diagnostic};
+use crate::{
+    config::Config,
+    meta::{meta_file, read_meta, record_changes},
+    policy::Policy,
+    read::Diagnostic,
+};
 use xml::{reader::ParserConfig, writer::EmitterConfig};
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -20,22 +28,25 @@ struct Items {
     Diagnostic: Vec<Diagnostic>,
 }
 
-pub fn write_diagnostics(
-    data: &Vec<Diagnostic>,
-    config: &Config,
-    model: &String,
-) -> Result<(), String> {
-    let modelsPath = Path::new(&config.modelpath);
+pub fn suppressions_file(config: &Config, model: &String) -> Result<PathBuf, String> {
+    if let Some(file) = config.suppressions(model) {
+        let supp_file_path = PathBuf::from(file);
+        if !supp_file_path.exists() {
+            return Err(format!("Suppressions file doesn't exist: {file}"));
+        }
+        return Ok(supp_file_path);
+    }
+    let modelsPath = Path::new(config.modelpath(model));
     if !modelsPath.exists() {
         return Err("Base model path in config doesn't exist".to_owned());
     }
-    let modelPath = modelsPath.join(&model);
+    let modelPath = modelsPath.join(model);
     if !modelPath.exists() {
         return Err("Model in config doesn't exist".to_owned());
     }
 
     let supp_file_path = modelPath
-        .join(&model)
+        .join(model)
         .join("AxIgnoreDiagnosticList")
         .join(format!("{}_BPSuppressions.xml", &model));
     if !supp_file_path.exists() {
@@ -44,7 +55,11 @@ pub fn write_diagnostics(
             supp_file_path.to_str().unwrap_or("N/A")
         ));
     }
-    let xml = match fs::read_to_string(&supp_file_path) {
+    Ok(supp_file_path)
+}
+
+fn load_suppressions(supp_file_path: &Path) -> Result<IgnoreDiagnostics, String> {
+    let xml = match fs::read_to_string(supp_file_path) {
         Ok(xml) => xml,
         Err(_) => {
             return Err(format!(
@@ -53,24 +68,60 @@ pub fn write_diagnostics(
             ))
         }
     };
-    let mut suppressions: IgnoreDiagnostics = match from_str(&xml) {
-        Ok(xml) => xml,
-        Err(_) => return Err("Could not parse suppression xml file".to_owned()),
+    match from_str(&xml) {
+        Ok(xml) => Ok(xml),
+        Err(_) => Err("Could not parse suppression xml file".to_owned()),
+    }
+}
+
+pub fn read_suppressions(config: &Config, model: &String) -> Result<Vec<Diagnostic>, String> {
+    let supp_file_path = suppressions_file(config, model)?;
+    Ok(load_suppressions(&supp_file_path)?.Items.Diagnostic)
+}
+
+/// Adds or updates suppressions for all justified diagnostics. New and
+/// changed suppressions get audit metadata, with `work_item` if given. With
+/// the review workflow enabled only approved justifications are written.
+pub fn write_diagnostics(
+    data: &Vec<Diagnostic>,
+    config: &Config,
+    model: &String,
+    work_item: Option<&str>,
+) -> Result<(), String> {
+    let supp_file_path = suppressions_file(config, model)?;
+    let mut suppressions = load_suppressions(&supp_file_path)?;
+    let mut changed = vec![];
+    let meta = match config.workflow {
+        true => read_meta(config, model)?,
+        false => vec![],
     };
 
     for item in data {
-        if item.Justification == "" {
+        if item.Justification.is_empty()
+            || Policy::of(config, model, &item.Moniker) == Policy::MustFix
+        {
+            continue;
+        }
+        if config.workflow
+            && !meta
+                .iter()
+                .any(|m| m.is_for(item) && m.is_approved(&item.Justification))
+        {
             continue;
         }
         if let Some(supp) = suppressions
             .Items
             .Diagnostic
             .iter_mut()
-            .find(|d| d.Path == item.Path && d.Moniker == item.Moniker)
+            .find(|d| d.same_as(item))
         {
+            if supp.Justification != item.Justification {
+                changed.push(item);
+            }
             supp.Justification = item.Justification.clone();
             // println!("Mod: {0:?}", supp);
         } else {
+            changed.push(item);
             let mut supp = item.clone();
             supp.Justification = item.Justification.clone();
             // println!("New: {0:?}", &supp);
@@ -90,10 +141,19 @@ pub fn write_diagnostics(
             return Err("Could not format suppressions".to_owned());
         }
     };
-    match fs::write(&supp_file_path, xml) {
-        Ok(_) => Ok(()),
-        Err(_) => Err("Could not write suppressions file".to_owned()),
+    // The audit entries are saved first, so suppressions are never written
+    // without them, and put back if the suppression file can't be written.
+    let meta_file = meta_file(&supp_file_path);
+    let previous = fs::read(&meta_file).ok();
+    record_changes(&supp_file_path, &changed, work_item)?;
+    if fs::write(&supp_file_path, xml).is_err() {
+        let _ = match previous {
+            Some(previous) => fs::write(&meta_file, previous),
+            None => fs::remove_file(&meta_file),
+        };
+        return Err("Could not write suppressions file".to_owned());
     }
+    Ok(())
 }
 
 fn format_xml(src: &[u8]) -> Result<String, xml::reader::Error> {